@@ -706,6 +706,44 @@ impl Vec2 {
     pub const fn is_zero(&self) -> bool {
         self.x.is_zero() && self.z.is_zero()
     }
+
+    /// The cross product of two vectors in raw (unshifted) units
+    ///
+    /// This is positive when `other` points counter-clockwise from `self`, negative when it points
+    /// clockwise, and 0 when the vectors are parallel.
+    pub const fn cross(&self, other: &Self) -> i64 {
+        self.x.0 as i64 * other.z.0 as i64 - self.z.0 as i64 * other.x.0 as i64
+    }
+
+    /// The dot product of two vectors in raw (unshifted) units
+    pub const fn dot(&self, other: &Self) -> i64 {
+        self.x.0 as i64 * other.x.0 as i64 + self.z.0 as i64 * other.z.0 as i64
+    }
+
+    /// Check whether this point lies inside (or on the edge of) a convex polygon, such as a camera
+    /// zone or AOT quad
+    ///
+    /// Like the game, this checks which side of each edge the point falls on, so the polygon must
+    /// be convex, but its points may be wound in either direction. A polygon with no area contains
+    /// no points.
+    pub fn in_convex_polygon(&self, points: &[Self]) -> bool {
+        let edges = || (0..points.len()).map(|i| (points[i], points[(i + 1) % points.len()]));
+
+        let area: i128 = edges().map(|(start, end)| start.cross(&end) as i128).sum();
+        if area == 0 {
+            return false;
+        }
+
+        let mut has_positive = false;
+        let mut has_negative = false;
+        for (start, end) in edges() {
+            let cross = (end - start).cross(&(*self - start));
+            has_positive |= cross > 0;
+            has_negative |= cross < 0;
+        }
+
+        !(has_positive && has_negative)
+    }
 }
 
 impl std::ops::Add for Vec2 {
@@ -1173,4 +1211,22 @@ mod tests {
         let angle = zombie_pos.angle_between(&player_pos);
         assert_eq!(angle, Fixed32(3565));
     }
+
+    #[test]
+    fn test_in_convex_polygon() {
+        let square = [Vec2::new(-1000, -1000), Vec2::new(1000, -1000), Vec2::new(1000, 1000), Vec2::new(-1000, 1000)];
+        assert!(Vec2::new(0, 0).in_convex_polygon(&square));
+        assert!(Vec2::new(1000, 0).in_convex_polygon(&square));
+        assert!(!Vec2::new(1001, 0).in_convex_polygon(&square));
+
+        let mut reversed = square;
+        reversed.reverse();
+        assert!(Vec2::new(0, 0).in_convex_polygon(&reversed));
+
+        let point = [Vec2::new(500, 500); 4];
+        assert!(!Vec2::new(500, 500).in_convex_polygon(&point));
+        assert!(!Vec2::new(0, 0).in_convex_polygon(&point));
+        let line = [Vec2::new(0, 0), Vec2::new(1000, 0), Vec2::new(1000, 0), Vec2::new(0, 0)];
+        assert!(!Vec2::new(500, 0).in_convex_polygon(&line));
+    }
 }
@@ -4,16 +4,39 @@ pub use animation::*;
 mod aot;
 pub use aot::*;
 
+mod camera;
+pub use camera::*;
+
 mod character;
 pub use character::*;
 
+mod collision;
+pub use collision::*;
+
+mod export;
+
+mod items;
+pub use items::*;
+
+mod light;
+pub use light::*;
+
+mod message;
+pub use message::*;
+
 mod rdt;
 pub use rdt::*;
 
+mod rooms;
+pub use rooms::*;
+
 mod script;
 pub use script::*;
 
 mod weapon;
 pub use weapon::*;
 
+mod xref;
+pub use xref::*;
+
 pub const VSYNCS_PER_SECOND: u64 = 60;
\ No newline at end of file
@@ -1,13 +1,121 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
-pub const SAT_TRIGGER_BY_PLAYER: u8 = 0x01;
-pub const SAT_TRIGGER_BY_NPC: u8 = 0x02;
-pub const SAT_TRIGGER_BY_OBJECT: u8 = 0x04;
-pub const SAT_TRIGGER_BY_ALLY: u8 = 0x08;
-pub const SAT_TRIGGER_ON_ACTION: u8 = 0x10;
-pub const SAT_TRIGGER_FRONT: u8 = 0x20;
-pub const SAT_TRIGGER_CENTER: u8 = 0x40;
-pub const SAT_4P: u8 = 0x80;
+use crate::common::*;
+use super::script::{Instruction, ScriptKind};
+
+/// The SAT (set attribute) flags of an AOT, which control what triggers it and how
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SatFlags(u8);
+
+impl SatFlags {
+    pub const TRIGGER_BY_PLAYER: Self = Self(0x01);
+    pub const TRIGGER_BY_NPC: Self = Self(0x02);
+    pub const TRIGGER_BY_OBJECT: Self = Self(0x04);
+    pub const TRIGGER_BY_ALLY: Self = Self(0x08);
+    pub const TRIGGER_ON_ACTION: Self = Self(0x10);
+    pub const TRIGGER_FRONT: Self = Self(0x20);
+    pub const TRIGGER_CENTER: Self = Self(0x40);
+    /// The AOT's area is a quadrilateral rather than a rectangle
+    pub const FOUR_POINTS: Self = Self(0x80);
+
+    const NAMES: [(Self, &'static str); 8] = [
+        (Self::TRIGGER_BY_PLAYER, "TRIGGER_BY_PLAYER"),
+        (Self::TRIGGER_BY_NPC, "TRIGGER_BY_NPC"),
+        (Self::TRIGGER_BY_OBJECT, "TRIGGER_BY_OBJECT"),
+        (Self::TRIGGER_BY_ALLY, "TRIGGER_BY_ALLY"),
+        (Self::TRIGGER_ON_ACTION, "TRIGGER_ON_ACTION"),
+        (Self::TRIGGER_FRONT, "TRIGGER_FRONT"),
+        (Self::TRIGGER_CENTER, "TRIGGER_CENTER"),
+        (Self::FOUR_POINTS, "FOUR_POINTS"),
+    ];
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every flag in `other` is set
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether any flag in `other` is set
+    pub const fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub const fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl From<u8> for SatFlags {
+    fn from(bits: u8) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<SatFlags> for u8 {
+    fn from(flags: SatFlags) -> Self {
+        flags.0
+    }
+}
+
+impl BitOr for SatFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for SatFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+impl BitAnd for SatFlags {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Debug for SatFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SatFlags(")?;
+        let names: Vec<_> = Self::NAMES.iter().filter(|(flag, _)| self.contains(*flag)).map(|(_, name)| *name).collect();
+        f.write_str(&names.join(" | "))?;
+        if names.is_empty() {
+            f.write_str("empty")?;
+        }
+        f.write_str(")")
+    }
+}
 
 /// ID of an item in the game
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
@@ -308,4 +416,431 @@ impl From<u8> for SceType {
             _ => Self::Unknown,
         }
     }
-}
\ No newline at end of file
+}
+
+/// The area covered by an AOT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AotGeometry {
+    /// An axis-aligned rectangle with its minimum corner at `x`, `z`
+    Rect { x: Fixed16, z: Fixed16, w: UFixed16, h: UFixed16 },
+    /// A quadrilateral given by its corners in order
+    Quad([Vec2; 4]),
+}
+
+impl AotGeometry {
+    /// The corners of the area, in order around its edge
+    pub fn corners(&self) -> [Vec2; 4] {
+        match *self {
+            Self::Rect { x, z, w, h } => {
+                let min = Vec2::new(x, z);
+                let max = min + (w.to_32(), h.to_32());
+                [min, Vec2::new(max.x, min.z), max, Vec2::new(min.x, max.z)]
+            }
+            Self::Quad(corners) => corners,
+        }
+    }
+
+    /// The average of the area's corners
+    pub fn center(&self) -> Vec2 {
+        let corners = self.corners();
+        let x: i64 = corners.iter().map(|corner| corner.x.0 as i64).sum();
+        let z: i64 = corners.iter().map(|corner| corner.z.0 as i64).sum();
+        Vec2::new(Fixed32((x / 4) as i32), Fixed32((z / 4) as i32))
+    }
+
+    /// Check whether a point lies inside (or on the edge of) the area
+    ///
+    /// Quads are tested with [`Vec2::in_convex_polygon`], so they must be convex and have some
+    /// area.
+    pub fn contains(&self, point: Vec2) -> bool {
+        match *self {
+            Self::Rect { .. } => {
+                let [min, _, max, _] = self.corners();
+                point.x >= min.x && point.x <= max.x && point.z >= min.z && point.z <= max.z
+            }
+            Self::Quad(corners) => point.in_convex_polygon(&corners),
+        }
+    }
+}
+
+/// A character that may set off AOTs
+///
+/// An AOT fires for a character when:
+/// - its SAT includes one of the `TRIGGER_BY_*` flags in `source`,
+/// - the action button is pressed, if the SAT has [`SatFlags::TRIGGER_ON_ACTION`], and
+/// - one of the character's check points is inside the AOT. With [`SatFlags::TRIGGER_FRONT`] the
+///   front point, `front_distance` ahead of the character, is checked, and with
+///   [`SatFlags::TRIGGER_CENTER`] the character's position is checked. If neither flag is set, the
+///   character's position is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AotTrigger {
+    /// The `TRIGGER_BY_*` flags this character counts as
+    pub source: SatFlags,
+    pub position: Vec2,
+    /// The character's facing angle, where 0x1000 is a full turn
+    pub angle: Fixed32,
+    pub action: bool,
+    pub front_distance: Fixed32,
+}
+
+impl AotTrigger {
+    /// How far ahead of the player the front point is
+    pub const PLAYER_FRONT_DISTANCE: Fixed32 = Fixed32(400);
+
+    /// The player at the given position and facing angle
+    pub const fn player(position: Vec2, angle: Fixed32, action: bool) -> Self {
+        Self {
+            source: SatFlags::TRIGGER_BY_PLAYER,
+            position,
+            angle,
+            action,
+            front_distance: Self::PLAYER_FRONT_DISTANCE,
+        }
+    }
+
+    /// The point `front_distance` ahead of the character in the direction it's facing
+    pub fn front_point(&self) -> Vec2 {
+        self.position + Vec2::new(Fixed32(0), self.front_distance).rotate_y(self.angle)
+    }
+
+    /// Decide whether an AOT with the given geometry and SAT fires for this character
+    pub fn fires(&self, geometry: &AotGeometry, sat: SatFlags) -> bool {
+        if !sat.intersects(self.source) {
+            return false;
+        }
+
+        if sat.contains(SatFlags::TRIGGER_ON_ACTION) && !self.action {
+            return false;
+        }
+
+        let check_front = sat.contains(SatFlags::TRIGGER_FRONT);
+        let check_center = sat.contains(SatFlags::TRIGGER_CENTER) || !check_front;
+        (check_front && geometry.contains(self.front_point())) || (check_center && geometry.contains(self.position))
+    }
+}
+
+/// The fields shared by every AOT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AotBase {
+    /// The AOT's index in the room's AOT table
+    pub id: u8,
+    pub sce: SceType,
+    pub sat: SatFlags,
+    pub floor: u8,
+    pub super_: u8,
+    pub geometry: AotGeometry,
+}
+
+/// Where a door leads and what's needed to open it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DoorAot {
+    pub next_pos: Vec3,
+    pub next_dir_y: Fixed16,
+    pub next_stage: u8,
+    pub next_room: u8,
+    pub next_cut: u8,
+    pub next_floor: u8,
+    pub dtex_type: u8,
+    pub door_type: u8,
+    pub knock_type: u8,
+    pub key_id: u8,
+    pub key_type: u8,
+}
+
+/// An item that can be picked up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemAot {
+    /// The item's ID, which may not be a known [`Item`]
+    pub item_id: u16,
+    pub quantity: u16,
+    /// The item flag that's set once the item has been picked up
+    pub flag: u16,
+    /// The model shown for the item
+    pub md1: u8,
+    pub action: u8,
+}
+
+impl ItemAot {
+    pub fn item(&self) -> Option<Item> {
+        Item::try_from(self.item_id).ok()
+    }
+}
+
+/// An AOT (area of trigger) set up by a room script
+///
+/// `DoorAotSet` and `ItemAotSet` instructions always produce doors and items, while the AOTs
+/// produced by `AotSet` are classified by their [`SceType`], with their three data fields kept as
+/// they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aot {
+    Door { base: AotBase, door: DoorAot },
+    Item { base: AotBase, item: ItemAot },
+    Message { base: AotBase, data: [u16; 3] },
+    Event { base: AotBase, data: [u16; 3] },
+    Save { base: AotBase, data: [u16; 3] },
+    ItemBox { base: AotBase, data: [u16; 3] },
+    Other { base: AotBase, data: [u16; 3] },
+}
+
+impl Aot {
+    /// Get the AOT an instruction sets, if it sets one
+    pub fn from_instruction(instruction: &Instruction) -> Option<Self> {
+        let base = |aot: u8, sce: u8, sat: u8, floor: u8, super_: u8, geometry: AotGeometry| AotBase {
+            id: aot,
+            sce: SceType::from(sce),
+            sat: SatFlags::from(sat),
+            floor,
+            super_,
+            geometry,
+        };
+        let quad = |corners: [(Fixed16, Fixed16); 4]| AotGeometry::Quad(corners.map(Vec2::from));
+
+        Some(match *instruction {
+            Instruction::AotSet { aot, sce, sat, n_floor, super_, x, z, w, h, data0, data1, data2 } => {
+                let base = base(aot as u8, sce, sat, n_floor, super_, AotGeometry::Rect { x, z, w, h });
+                Self::from_data(base, [data0, data1, data2])
+            }
+            Instruction::AotSet4p {
+                aot, sce, sat, n_floor, super_, x0, z0, x1, z1, x2, z2, x3, z3, data0, data1, data2,
+            } => {
+                let base = base(aot, sce, sat, n_floor, super_, quad([(x0, z0), (x1, z1), (x2, z2), (x3, z3)]));
+                Self::from_data(base, [data0, data1, data2])
+            }
+            Instruction::DoorAotSet {
+                aot, sce, sat, n_floor, super_, x, z, w, h, next_pos_x, next_pos_y, next_pos_z, next_cdir_y, next_stage,
+                next_room, next_cut, next_nfloor, dtex_type, door_type, knock_type, key_id, key_type, ..
+            } => Self::Door {
+                base: base(aot, sce, sat, n_floor, super_, AotGeometry::Rect { x, z, w, h }),
+                door: DoorAot {
+                    next_pos: Vec3::new(next_pos_x, next_pos_y, next_pos_z),
+                    next_dir_y: next_cdir_y,
+                    next_stage,
+                    next_room,
+                    next_cut,
+                    next_floor: next_nfloor,
+                    dtex_type,
+                    door_type,
+                    knock_type,
+                    key_id,
+                    key_type,
+                },
+            },
+            Instruction::DoorAotSet4p {
+                aot, sce, sat, n_floor, super_, x0, z0, x1, z1, x2, z2, x3, z3, next_pos_x, next_pos_y, next_pos_z,
+                next_cdir_y, next_stage, next_room, next_cut, next_nfloor, dtex_type, door_type, knock_type, key_id,
+                key_type, ..
+            } => Self::Door {
+                base: base(aot, sce, sat, n_floor, super_, quad([(x0, z0), (x1, z1), (x2, z2), (x3, z3)])),
+                door: DoorAot {
+                    next_pos: Vec3::new(next_pos_x, next_pos_y, next_pos_z),
+                    next_dir_y: next_cdir_y,
+                    next_stage,
+                    next_room,
+                    next_cut,
+                    next_floor: next_nfloor,
+                    dtex_type,
+                    door_type,
+                    knock_type,
+                    key_id,
+                    key_type,
+                },
+            },
+            Instruction::ItemAotSet { aot, sce, sat, n_floor, super_, x, z, w, h, i_item, n_item, flag, md1, action }
+            | Instruction::ItemAotSet2 { aot, sce, sat, n_floor, super_, x, z, w, h, i_item, n_item, flag, md1, action, .. } => Self::Item {
+                base: base(aot, sce, sat, n_floor, super_, AotGeometry::Rect { x, z, w, h }),
+                item: ItemAot { item_id: i_item, quantity: n_item, flag, md1, action },
+            },
+            Instruction::ItemAotSet4p {
+                aot, sce, sat, n_floor, super_, x0, z0, x1, z1, x2, z2, x3, z3, i_item, n_item, flag, md1, action,
+            } => Self::Item {
+                base: base(aot, sce, sat, n_floor, super_, quad([(x0, z0), (x1, z1), (x2, z2), (x3, z3)])),
+                item: ItemAot { item_id: i_item, quantity: n_item, flag, md1, action },
+            },
+            _ => return None,
+        })
+    }
+
+    fn from_data(base: AotBase, data: [u16; 3]) -> Self {
+        match base.sce {
+            SceType::Message => Self::Message { base, data },
+            SceType::Event => Self::Event { base, data },
+            SceType::Save => Self::Save { base, data },
+            SceType::ItemBox => Self::ItemBox { base, data },
+            _ => Self::Other { base, data },
+        }
+    }
+
+    pub const fn base(&self) -> &AotBase {
+        match self {
+            Self::Door { base, .. } | Self::Item { base, .. } | Self::Message { base, .. } | Self::Event { base, .. }
+            | Self::Save { base, .. } | Self::ItemBox { base, .. } | Self::Other { base, .. } => base,
+        }
+    }
+
+    pub const fn id(&self) -> u8 {
+        self.base().id
+    }
+
+    pub const fn sce(&self) -> SceType {
+        self.base().sce
+    }
+
+    pub const fn sat(&self) -> SatFlags {
+        self.base().sat
+    }
+
+    pub const fn geometry(&self) -> &AotGeometry {
+        &self.base().geometry
+    }
+
+    /// Decide whether this AOT fires for a character
+    pub fn fires(&self, trigger: &AotTrigger) -> bool {
+        trigger.fires(self.geometry(), self.sat())
+    }
+}
+
+/// A change an `AotReset` instruction makes to an existing AOT's type, flags, and data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AotReset {
+    pub id: u8,
+    pub sce: SceType,
+    pub sat: SatFlags,
+    pub data: [i16; 3],
+}
+
+/// An AOT being set or reset by a room script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AotChange {
+    Set(Aot),
+    Reset(AotReset),
+}
+
+impl AotChange {
+    pub fn from_instruction(instruction: &Instruction) -> Option<Self> {
+        match *instruction {
+            Instruction::AotReset { aot, sce, sat, data0, data1, data2 } => Some(Self::Reset(AotReset {
+                id: aot as u8,
+                sce: SceType::from(sce),
+                sat: SatFlags::from(sat),
+                data: [data0, data1, data2],
+            })),
+            _ => Aot::from_instruction(instruction).map(Self::Set),
+        }
+    }
+
+    pub const fn id(&self) -> u8 {
+        match self {
+            Self::Set(aot) => aot.id(),
+            Self::Reset(reset) => reset.id,
+        }
+    }
+}
+
+/// An AOT change along with where it appears in a room's scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScriptAot {
+    pub script: ScriptKind,
+    pub function: usize,
+    /// Byte offset of the instruction from the start of the function
+    pub offset: usize,
+    pub change: AotChange,
+}
+
+impl ScriptAot {
+    /// Collect the AOTs set and reset by a script
+    pub fn collect(script: ScriptKind, functions: &[Vec<Instruction>]) -> Vec<Self> {
+        let mut aots = Vec::new();
+        for (function_index, function) in functions.iter().enumerate() {
+            let mut offset = 0usize;
+            for instruction in function {
+                if let Some(change) = AotChange::from_instruction(instruction) {
+                    aots.push(Self { script, function: function_index, offset, change });
+                }
+                offset += instruction.size();
+            }
+        }
+        aots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sat_flags() {
+        let mut sat = SatFlags::TRIGGER_BY_PLAYER | SatFlags::TRIGGER_ON_ACTION;
+        assert_eq!(sat.bits(), 0x11);
+        assert!(sat.contains(SatFlags::TRIGGER_BY_PLAYER));
+        assert!(!sat.contains(SatFlags::TRIGGER_BY_PLAYER | SatFlags::FOUR_POINTS));
+        sat.remove(SatFlags::TRIGGER_BY_PLAYER);
+        sat |= SatFlags::FOUR_POINTS;
+        assert_eq!(u8::from(sat), 0x90);
+        assert_eq!(format!("{:?}", sat), "SatFlags(TRIGGER_ON_ACTION | FOUR_POINTS)");
+        assert_eq!(format!("{:?}", SatFlags::empty()), "SatFlags(empty)");
+    }
+
+    #[test]
+    fn test_aots_from_script() {
+        let functions = vec![Instruction::assemble("
+            aot_set aot=0 sce=Message sat=0x11 x=-1000 z=2000 w=1500 h=500 data0=3
+            item_aot_set4p aot=1 sce=Item sat=0x91 x0=0 z0=0 x1=100 z1=0 x2=100 z2=100 x3=0 z3=100 i_item=HandgunAmmo n_item=15 flag=7 md1=2
+            aot_reset aot=0 sce=Auto sat=0
+            evt_end
+        ").unwrap()];
+        let aots = ScriptAot::collect(ScriptKind::Init, &functions);
+        assert_eq!(aots.len(), 3);
+
+        let AotChange::Set(Aot::Message { base, data }) = aots[0].change else {
+            panic!("expected a message AOT");
+        };
+        assert_eq!(base.sat, SatFlags::TRIGGER_BY_PLAYER | SatFlags::TRIGGER_ON_ACTION);
+        assert_eq!(data, [3, 0, 0]);
+        assert_eq!(base.geometry.corners()[2], Vec2::new(Fixed16(500), Fixed16(2500)));
+        assert_eq!(base.geometry.center(), Vec2::new(Fixed16(-250), Fixed16(2250)));
+
+        let AotChange::Set(Aot::Item { base, item }) = aots[1].change else {
+            panic!("expected an item AOT");
+        };
+        assert_eq!(aots[1].offset, 20);
+        assert!(base.sat.contains(SatFlags::FOUR_POINTS));
+        assert_eq!(base.geometry.corners()[1], Vec2::new(Fixed16(100), Fixed16(0)));
+        assert_eq!(item.item(), Some(Item::HandgunAmmo));
+        assert_eq!((item.quantity, item.flag, item.md1), (15, 7, 2));
+
+        assert_eq!(aots[2].change, AotChange::Reset(AotReset { id: 0, sce: SceType::Auto, sat: SatFlags::empty(), data: [0; 3] }));
+    }
+
+    #[test]
+    fn test_trigger() {
+        let rect = AotGeometry::Rect { x: Fixed16(0), z: Fixed16(1000), w: UFixed16(1000), h: UFixed16(1000) };
+        let action_front = SatFlags::TRIGGER_BY_PLAYER | SatFlags::TRIGGER_ON_ACTION | SatFlags::TRIGGER_FRONT;
+
+        // standing just below the rectangle, facing into it
+        let mut player = AotTrigger::player(Vec2::new(Fixed32(500), Fixed32(800)), Fixed32(0), false);
+        assert_eq!(player.front_point(), Vec2::new(Fixed32(500), Fixed32(1200)));
+        assert!(!player.fires(&rect, action_front));
+        player.action = true;
+        assert!(player.fires(&rect, action_front));
+        assert!(!player.fires(&rect, SatFlags::TRIGGER_BY_PLAYER | SatFlags::TRIGGER_CENTER));
+        assert!(!player.fires(&rect, SatFlags::TRIGGER_BY_NPC | SatFlags::TRIGGER_FRONT));
+
+        // turned around, the front point is outside
+        player.angle = Fixed32(0x800);
+        assert!(!player.fires(&rect, action_front));
+
+        // the center check only needs the player to be inside
+        player.position = Vec2::new(Fixed32(500), Fixed32(1500));
+        assert!(player.fires(&rect, SatFlags::TRIGGER_BY_PLAYER));
+        assert!(player.fires(&rect, action_front | SatFlags::TRIGGER_CENTER));
+
+        let quad = AotGeometry::Quad([
+            Vec2::new(Fixed32(0), Fixed32(0)),
+            Vec2::new(Fixed32(1000), Fixed32(500)),
+            Vec2::new(Fixed32(1000), Fixed32(1500)),
+            Vec2::new(Fixed32(0), Fixed32(1000)),
+        ]);
+        assert!(quad.contains(Vec2::new(Fixed32(500), Fixed32(500))));
+        assert!(!quad.contains(Vec2::new(Fixed32(900), Fixed32(100))));
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use anyhow::Result;
+use binrw::{binrw, BinReaderExt, BinWriterExt};
+
+use crate::common::*;
+
+/// A fixed camera angle ("cut") in a room
+///
+/// Positions are in world coordinates. The mask offset is an offset from the start of the RDT file
+/// to the sprite data used to mask 3D objects behind the background for this camera, or 0 if the
+/// camera has no mask.
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Camera {
+    pub flags: u16,
+    pub projection_distance: u16,
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub mask_offset: u32,
+}
+
+impl Camera {
+    pub const fn has_mask(&self) -> bool {
+        self.mask_offset != 0
+    }
+}
+
+/// The floor value of a camera zone that applies to all floors
+pub const ALL_FLOORS: u8 = 0xFF;
+const ZONE_LIST_END: u8 = 0xFF;
+
+/// A quadrilateral region that switches the camera when the player enters it
+///
+/// While the current camera is `from_cut`, the game switches to `to_cut` as soon as the player
+/// steps inside the zone. The zone only applies to characters on the same floor unless the floor
+/// is [`ALL_FLOORS`].
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CameraZone {
+    pub flags: u8,
+    pub floor: u8,
+    pub from_cut: u8,
+    pub to_cut: u8,
+    pub x0: Fixed16,
+    pub z0: Fixed16,
+    pub x1: Fixed16,
+    pub z1: Fixed16,
+    pub x2: Fixed16,
+    pub z2: Fixed16,
+    pub x3: Fixed16,
+    pub z3: Fixed16,
+}
+
+impl CameraZone {
+    pub fn points(&self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.x0, self.z0),
+            Vec2::new(self.x1, self.z1),
+            Vec2::new(self.x2, self.z2),
+            Vec2::new(self.x3, self.z3),
+        ]
+    }
+
+    pub const fn applies_to_floor(&self, floor: u8) -> bool {
+        self.floor == ALL_FLOORS || self.floor == floor
+    }
+
+    /// Check whether a point lies inside (or on the edge of) this zone
+    ///
+    /// See [`Vec2::in_convex_polygon`].
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.in_convex_polygon(&self.points())
+    }
+
+    /// Read a list of camera zones terminated by an end marker
+    pub fn read_list<T: Read + Seek>(mut f: T) -> Result<Vec<Self>> {
+        let mut zones = Vec::new();
+        loop {
+            let start = f.stream_position()?;
+            let flags: u8 = f.read_le()?;
+            if flags == ZONE_LIST_END {
+                break;
+            }
+
+            f.seek(SeekFrom::Start(start))?;
+            zones.push(f.read_le()?);
+        }
+
+        Ok(zones)
+    }
+
+    /// Write a list of camera zones followed by the end marker
+    pub fn write_list<T: Write + Seek>(zones: &[Self], mut f: T) -> Result<()> {
+        f.write_le(&zones)?;
+        f.write_le(&u32::MAX)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn square_zone() -> CameraZone {
+        CameraZone {
+            flags: 0,
+            floor: ALL_FLOORS,
+            from_cut: 0,
+            to_cut: 1,
+            x0: Fixed16(-1000),
+            z0: Fixed16(-1000),
+            x1: Fixed16(1000),
+            z1: Fixed16(-1000),
+            x2: Fixed16(1000),
+            z2: Fixed16(1000),
+            x3: Fixed16(-1000),
+            z3: Fixed16(1000),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let camera = Camera {
+            flags: 0,
+            projection_distance: 0x2ee,
+            eye: Vec3::new(-1200, -2050, 8400),
+            target: Vec3::new(-900, -1800, 1200),
+            mask_offset: 0x1234,
+        };
+
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(&camera).unwrap();
+        let buf = writer.into_inner();
+        assert_eq!(buf.len(), 0x20);
+        assert_eq!(buf.len(), size_of::<Camera>());
+
+        let parsed: Camera = Cursor::new(buf).read_le().unwrap();
+        assert_eq!(parsed, camera);
+    }
+
+    #[test]
+    fn test_zone_contains() {
+        let zone = square_zone();
+        assert!(zone.contains(Vec2::zero()));
+        assert!(zone.contains(Vec2::new(1000, 0)));
+        assert!(!zone.contains(Vec2::new(1001, 0)));
+        assert!(!zone.contains(Vec2::new(0, -2000)));
+    }
+
+    #[test]
+    fn test_zone_list() {
+        let zones = vec![square_zone(), square_zone()];
+        let mut writer = Cursor::new(Vec::new());
+        CameraZone::write_list(&zones, &mut writer).unwrap();
+        let buf = writer.into_inner();
+        assert_eq!(buf.len(), 0x14 * 2 + 4);
+
+        let parsed = CameraZone::read_list(Cursor::new(buf)).unwrap();
+        assert_eq!(parsed, zones);
+    }
+}
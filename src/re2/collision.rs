@@ -0,0 +1,368 @@
+use std::io::Cursor;
+
+use anyhow::{bail, Result};
+use binrw::{binrw, BinWriterExt};
+
+use crate::common::*;
+
+/// The shape of a collider within its bounding box
+///
+/// Triangles are named for the corner that holds the right angle, with x increasing to the right
+/// and z increasing upward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColliderShape {
+    Rectangle,
+    TriangleTopRight,
+    TriangleTopLeft,
+    TriangleBottomRight,
+    TriangleBottomLeft,
+    Rhombus,
+    Circle,
+    OvalX,
+    OvalZ,
+    ClimbUp,
+    JumpDown,
+    Slope,
+    Stairs,
+    Unknown(u8),
+}
+
+impl ColliderShape {
+    const MASK: u16 = 0x000f;
+
+    pub const fn from_mask(collision_mask: u16) -> Self {
+        match (collision_mask & Self::MASK) as u8 {
+            0 => Self::Rectangle,
+            1 => Self::TriangleTopRight,
+            2 => Self::TriangleTopLeft,
+            3 => Self::TriangleBottomRight,
+            4 => Self::TriangleBottomLeft,
+            5 => Self::Rhombus,
+            6 => Self::Circle,
+            7 => Self::OvalX,
+            8 => Self::OvalZ,
+            9 => Self::ClimbUp,
+            10 => Self::JumpDown,
+            11 => Self::Slope,
+            12 => Self::Stairs,
+            bits => Self::Unknown(bits),
+        }
+    }
+
+    pub const fn bits(&self) -> u8 {
+        match self {
+            Self::Rectangle => 0,
+            Self::TriangleTopRight => 1,
+            Self::TriangleTopLeft => 2,
+            Self::TriangleBottomRight => 3,
+            Self::TriangleBottomLeft => 4,
+            Self::Rhombus => 5,
+            Self::Circle => 6,
+            Self::OvalX => 7,
+            Self::OvalZ => 8,
+            Self::ClimbUp => 9,
+            Self::JumpDown => 10,
+            Self::Slope => 11,
+            Self::Stairs => 12,
+            Self::Unknown(bits) => *bits & Self::MASK as u8,
+        }
+    }
+
+    pub const fn is_triangle(&self) -> bool {
+        matches!(self, Self::TriangleTopRight | Self::TriangleTopLeft | Self::TriangleBottomRight | Self::TriangleBottomLeft)
+    }
+
+    pub const fn is_round(&self) -> bool {
+        matches!(self, Self::Circle | Self::OvalX | Self::OvalZ)
+    }
+}
+
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collider {
+    pub x: Fixed16,
+    pub z: Fixed16,
+    pub w: UFixed16,
+    pub h: UFixed16,
+    pub collision_mask: u16,
+    pub quadrant_mask: u16,
+    pub floor: u32,
+}
+
+impl Collider {
+    pub const fn collision_mask(&self) -> u16 {
+        self.collision_mask & 0xfff0
+    }
+
+    pub const fn shape(&self) -> ColliderShape {
+        ColliderShape::from_mask(self.collision_mask)
+    }
+
+    pub const fn set_shape(&mut self, shape: ColliderShape) {
+        self.collision_mask = self.collision_mask() | shape.bits() as u16;
+    }
+
+    /// The corners of the collider's bounding box, counter-clockwise from the minimum corner
+    fn corners(&self) -> [Vec2; 4] {
+        let min = Vec2::new(self.x, self.z);
+        let max = min + (self.w.to_32(), self.h.to_32());
+        [min, Vec2::new(max.x, min.z), max, Vec2::new(min.x, max.z)]
+    }
+
+    /// The vertices of the collider's outline, or `None` if the shape is curved
+    pub fn polygon(&self) -> Option<Vec<Vec2>> {
+        let [c00, c10, c11, c01] = self.corners();
+        let center = c00 + (self.w.to_32() >> 1, self.h.to_32() >> 1);
+
+        Some(match self.shape() {
+            ColliderShape::TriangleTopRight => vec![c10, c11, c01],
+            ColliderShape::TriangleTopLeft => vec![c00, c11, c01],
+            ColliderShape::TriangleBottomRight => vec![c00, c10, c11],
+            ColliderShape::TriangleBottomLeft => vec![c00, c10, c01],
+            ColliderShape::Rhombus => vec![
+                Vec2 { x: center.x, z: c00.z },
+                Vec2 { x: c11.x, z: center.z },
+                Vec2 { x: center.x, z: c11.z },
+                Vec2 { x: c00.x, z: center.z },
+            ],
+            shape if shape.is_round() => return None,
+            _ => vec![c00, c10, c11, c01],
+        })
+    }
+
+    /// Check whether a point is inside (or on the edge of) the collider
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self.polygon() {
+            Some(points) => point.in_convex_polygon(&points),
+            None => {
+                // the ellipse inscribed in the bounding box, doubled so that its center is on a whole
+                // unit
+                let size = Vec2::new(self.w, self.h);
+                let offset = ((point - Vec2::new(self.x, self.z)) << 1) - size;
+                Self::in_ellipse(offset, size)
+            }
+        }
+    }
+
+    /// Check whether a circle (such as a character's collision radius) overlaps the collider
+    ///
+    /// For round shapes, the collider is grown by the radius along each axis, which is exact for
+    /// circles and a close approximation for ovals.
+    pub fn intersects(&self, center: Vec2, radius: Fixed32) -> bool {
+        if self.contains(center) {
+            return true;
+        }
+
+        let radius = radius.max(Fixed32(0));
+        match self.polygon() {
+            Some(points) => (0..points.len()).any(|i| {
+                Self::segment_within(points[i], points[(i + 1) % points.len()], center, radius)
+            }),
+            None => {
+                let size = Vec2::new(self.w, self.h);
+                let offset = ((center - Vec2::new(self.x, self.z)) << 1) - size;
+                Self::in_ellipse(offset, size + (radius << 1, radius << 1))
+            }
+        }
+    }
+
+    /// Check whether an offset from the center of an axis-aligned ellipse is inside it, given the
+    /// ellipse's full width and height
+    fn in_ellipse(offset: Vec2, size: Vec2) -> bool {
+        // the products need more than 64 bits
+        let (x, z) = (offset.x.0 as i128, offset.z.0 as i128);
+        let (w, h) = (size.x.0 as i128, size.z.0 as i128);
+        x * x * h * h + z * z * w * w <= w * w * h * h
+    }
+
+    /// Check whether a point is within `radius` of the line segment from `start` to `end`
+    fn segment_within(start: Vec2, end: Vec2, point: Vec2, radius: Fixed32) -> bool {
+        let edge = end - start;
+        let to_point = point - start;
+        let radius_squared = radius.0 as i64 * radius.0 as i64;
+
+        let projection = to_point.dot(&edge);
+        let len_squared = edge.dot(&edge);
+        if projection <= 0 || len_squared == 0 {
+            to_point.dot(&to_point) <= radius_squared
+        } else if projection >= len_squared {
+            let from_end = point - end;
+            from_end.dot(&from_end) <= radius_squared
+        } else {
+            // the distance from the line is cross / len, and squaring both sides needs more than
+            // 64 bits
+            let cross = edge.cross(&to_point) as i128;
+            cross * cross <= radius_squared as i128 * len_squared as i128
+        }
+    }
+}
+
+/// The collision data for a room
+///
+/// The collider count stored in the file is always derived from the number of colliders, so
+/// colliders can be freely added and removed before writing.
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub cell_x: Fixed16,
+    pub cell_z: Fixed16,
+    // the count includes the room boundary, which isn't stored as a collider
+    #[br(temp)]
+    #[bw(calc = colliders.len() as u32 + 1)]
+    count: u32,
+    pub ceiling: i32,
+    pub dummy: u32,
+    #[br(count = count.saturating_sub(1))]
+    pub colliders: Vec<Collider>,
+}
+
+impl Collision {
+    pub const fn new(cell_x: Fixed16, cell_z: Fixed16, ceiling: i32) -> Self {
+        Self {
+            cell_x,
+            cell_z,
+            ceiling,
+            dummy: 0,
+            colliders: Vec::new(),
+        }
+    }
+
+    pub fn set_cell(&mut self, cell_x: Fixed16, cell_z: Fixed16) {
+        self.cell_x = cell_x;
+        self.cell_z = cell_z;
+    }
+
+    pub fn add_collider(&mut self, collider: Collider) -> usize {
+        self.colliders.push(collider);
+        self.colliders.len() - 1
+    }
+
+    pub fn insert_collider(&mut self, index: usize, collider: Collider) -> Result<()> {
+        if index > self.colliders.len() {
+            bail!("Collider index {} is out of range (count {})", index, self.colliders.len());
+        }
+
+        self.colliders.insert(index, collider);
+        Ok(())
+    }
+
+    pub fn remove_collider(&mut self, index: usize) -> Option<Collider> {
+        (index < self.colliders.len()).then(|| self.colliders.remove(index))
+    }
+
+    pub fn collider_mut(&mut self, index: usize) -> Option<&mut Collider> {
+        self.colliders.get_mut(index)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(self)?;
+        Ok(writer.into_inner())
+    }
+}
+
+impl Default for Collision {
+    fn default() -> Self {
+        Self::new(Fixed16(0), Fixed16(0), 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use binrw::BinReaderExt;
+
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        assert_eq!(size_of::<Collider>(), 0x10);
+    }
+
+    fn collider(shape: ColliderShape) -> Collider {
+        let mut collider = Collider {
+            x: Fixed16(0),
+            z: Fixed16(0),
+            w: UFixed16(1000),
+            h: UFixed16(1000),
+            collision_mask: 0x0010,
+            quadrant_mask: 0,
+            floor: 0,
+        };
+        collider.set_shape(shape);
+        collider
+    }
+
+    #[test]
+    fn test_shape() {
+        let collider = collider(ColliderShape::Circle);
+        assert_eq!(collider.collision_mask, 0x0016);
+        assert_eq!(collider.collision_mask(), 0x0010);
+        assert_eq!(collider.shape(), ColliderShape::Circle);
+    }
+
+    #[test]
+    fn test_contains() {
+        let rect = collider(ColliderShape::Rectangle);
+        assert!(rect.contains(Vec2::new(100, 900)));
+        assert!(!rect.contains(Vec2::new(-1, 500)));
+
+        let triangle = collider(ColliderShape::TriangleBottomLeft);
+        assert!(triangle.contains(Vec2::new(100, 100)));
+        assert!(!triangle.contains(Vec2::new(900, 900)));
+
+        let triangle = collider(ColliderShape::TriangleTopRight);
+        assert!(!triangle.contains(Vec2::new(100, 100)));
+        assert!(triangle.contains(Vec2::new(900, 900)));
+        // on the hypotenuse
+        assert!(triangle.contains(Vec2::new(500, 500)));
+
+        let circle = collider(ColliderShape::Circle);
+        assert!(circle.contains(Vec2::new(500, 500)));
+        assert!(circle.contains(Vec2::new(500, 0)));
+        assert!(!circle.contains(Vec2::new(50, 50)));
+
+        let rhombus = collider(ColliderShape::Rhombus);
+        assert!(rhombus.contains(Vec2::new(500, 100)));
+        assert!(!rhombus.contains(Vec2::new(100, 100)));
+    }
+
+    #[test]
+    fn test_intersects() {
+        let rect = collider(ColliderShape::Rectangle);
+        assert!(rect.intersects(Vec2::new(-100, 500), Fixed32(150)));
+        assert!(!rect.intersects(Vec2::new(-100, 500), Fixed32(50)));
+        // corner
+        assert!(!rect.intersects(Vec2::new(-100, -100), Fixed32(100)));
+        assert!(rect.intersects(Vec2::new(-100, -100), Fixed32(150)));
+
+        let circle = collider(ColliderShape::Circle);
+        assert!(circle.intersects(Vec2::new(500, -100), Fixed32(100)));
+        assert!(!circle.intersects(Vec2::new(500, -100), Fixed32(99)));
+    }
+
+    #[test]
+    fn test_collision_count() {
+        let mut collision = Collision::new(Fixed16(-1000), Fixed16(2000), -1800);
+        let collider = Collider {
+            x: Fixed16(100),
+            z: Fixed16(200),
+            w: UFixed16(300),
+            h: UFixed16(400),
+            collision_mask: 0x0010,
+            quadrant_mask: 0,
+            floor: 0,
+        };
+        collision.add_collider(collider.clone());
+        collision.add_collider(collider.clone());
+        collision.remove_collider(0);
+
+        let buf = collision.to_bytes().unwrap();
+        assert_eq!(buf.len(), 0x10 + 0x10);
+        assert_eq!(u32::from_le_bytes(buf[4..8].try_into().unwrap()), 2);
+
+        let parsed: Collision = Cursor::new(buf).read_le().unwrap();
+        assert_eq!(parsed, collision);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+/// Quote and escape a string for use in JSON
+pub(crate) fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Join JSON values into an array
+pub(crate) fn json_array<I: IntoIterator<Item = String>>(values: I) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+/// A JSON object built one field at a time
+pub(crate) struct JsonObject(String);
+
+impl JsonObject {
+    pub(crate) fn new() -> Self {
+        Self(String::from("{"))
+    }
+
+    /// Add a field whose value is already JSON, such as a number or a nested object
+    pub(crate) fn field(mut self, name: &str, value: impl Display) -> Self {
+        if self.0.len() > 1 {
+            self.0.push(',');
+        }
+        self.0.push_str(&json_string(name));
+        self.0.push(':');
+        self.0.push_str(&value.to_string());
+        self
+    }
+
+    pub(crate) fn string(self, name: &str, value: &str) -> Self {
+        self.field(name, json_string(value))
+    }
+
+    /// Add a string field that is `null` if there is no value
+    pub(crate) fn optional_string(self, name: &str, value: Option<&str>) -> Self {
+        self.field(name, value.map_or_else(|| "null".to_string(), json_string))
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.0.push('}');
+        self.0
+    }
+}
+
+/// Escape a string for use in a quoted DOT identifier
+pub(crate) fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Quote a CSV field if it contains anything that would break the row
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a \"b\"\\\n\u{1}"), "\"a \\\"b\\\"\\\\\\n\\u0001\"");
+    }
+
+    #[test]
+    fn test_json_object() {
+        assert_eq!(JsonObject::new().finish(), "{}");
+        let inner = JsonObject::new().field("x", -1).finish();
+        let object = JsonObject::new()
+            .string("name", "a\"b")
+            .optional_string("item", None)
+            .field("position", inner)
+            .field("list", json_array([1, 2].map(|i| i.to_string())))
+            .finish();
+        assert_eq!(object, "{\"name\":\"a\\\"b\",\"item\":null,\"position\":{\"x\":-1},\"list\":[1,2]}");
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("Handgun Ammo"), "Handgun Ammo");
+        assert_eq!(csv_field("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+}
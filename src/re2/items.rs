@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Result};
+
+use super::aot::{Aot, AotChange, AotGeometry, Item, ScriptAot};
+use super::export::{csv_field, json_array, JsonObject};
+use super::rdt::{Rdt, RoomId};
+use super::script::{Instruction, ScriptKind};
+use crate::common::*;
+
+/// An item pickup set up by an `ItemAotSet`, `ItemAotSet4p`, or `ItemAotSet2` instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemPlacement {
+    pub room: RoomId,
+    pub script: ScriptKind,
+    pub function: usize,
+    /// Byte offset of the instruction from the start of the function
+    pub offset: usize,
+    /// The item's AOT index in the room
+    pub aot: u8,
+    /// The item's ID, which may not be a known [`Item`]
+    pub item_id: u16,
+    pub quantity: u16,
+    /// The item flag that's set once the item has been picked up
+    pub flag: u16,
+    pub floor: u8,
+    /// The model shown for the item
+    pub md1: u8,
+    pub geometry: AotGeometry,
+}
+
+/// What an item pickup should give instead of its original item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemReplacement {
+    pub item_id: u16,
+    pub quantity: u16,
+    /// The model shown for the item
+    pub md1: u8,
+}
+
+impl ItemReplacement {
+    pub fn new(item: Item, quantity: u16, md1: u8) -> Self {
+        Self { item_id: item.into(), quantity, md1 }
+    }
+}
+
+impl ItemPlacement {
+    const CSV_HEADER: &'static str = "room,script,function,offset,aot,item_id,item,quantity,flag,floor,x,z,md1";
+
+    /// Find the item pickups among a room's AOTs
+    pub fn from_aots(room: RoomId, aots: &[ScriptAot]) -> Vec<Self> {
+        aots.iter().filter_map(|aot| match aot.change {
+            AotChange::Set(Aot::Item { base, item }) => Some(Self {
+                room,
+                script: aot.script,
+                function: aot.function,
+                offset: aot.offset,
+                aot: base.id,
+                item_id: item.item_id,
+                quantity: item.quantity,
+                flag: item.flag,
+                floor: base.floor,
+                md1: item.md1,
+                geometry: base.geometry,
+            }),
+            _ => None,
+        }).collect()
+    }
+
+    /// Find every item pickup in a set of rooms, in the order the rooms are given
+    pub fn scan<'a, I: IntoIterator<Item = (RoomId, &'a Rdt)>>(rooms: I) -> Vec<Self> {
+        rooms.into_iter().flat_map(|(room, rdt)| Self::from_aots(room, &rdt.aots())).collect()
+    }
+
+    pub fn item(&self) -> Option<Item> {
+        Item::try_from(self.item_id).ok()
+    }
+
+    /// The center of the item's AOT
+    pub fn position(&self) -> Vec2 {
+        self.geometry.center()
+    }
+
+    /// Change the item this placement gives in its room's RDT
+    ///
+    /// The `ItemAotSet*` instruction is rewritten in place with [`Rdt::patch_instruction`], so the
+    /// rest of the RDT is unchanged. Fails if the instruction at the placement's location isn't the
+    /// item pickup the placement describes, such as when the placement came from a different
+    /// version of the room.
+    pub fn replace(&self, rdt: &mut Rdt, replacement: ItemReplacement) -> Result<()> {
+        let location = format!("Room {} {:?} function {} offset {:#x}", self.room, self.script, self.function, self.offset);
+        let instruction = rdt.instruction_at(self.script, self.function, self.offset)
+            .ok_or_else(|| anyhow!("{} is not the start of an instruction", location))?;
+        let current = AotChange::from_instruction(instruction).map(|change| ScriptAot {
+            script: self.script,
+            function: self.function,
+            offset: self.offset,
+            change,
+        });
+        if !current.is_some_and(|aot| Self::from_aots(self.room, &[aot]).first() == Some(self)) {
+            bail!("{} does not match the item placement", location);
+        }
+
+        let mut instruction = instruction.clone();
+        match &mut instruction {
+            Instruction::ItemAotSet { i_item, n_item, md1, .. }
+            | Instruction::ItemAotSet4p { i_item, n_item, md1, .. }
+            | Instruction::ItemAotSet2 { i_item, n_item, md1, .. } => {
+                *i_item = replacement.item_id;
+                *n_item = replacement.quantity;
+                *md1 = replacement.md1;
+            }
+            _ => unreachable!("item placements only come from item AOT instructions"),
+        }
+
+        rdt.patch_instruction(self.script, self.function, self.offset, instruction)
+    }
+
+    /// Change the items given by a set of placements across the rooms they're in
+    ///
+    /// Every placement is checked before any room is changed, so on error the rooms are left as
+    /// they were.
+    pub fn replace_all<'a, I>(rooms: &mut BTreeMap<RoomId, Rdt>, replacements: I) -> Result<()>
+    where I: IntoIterator<Item = (&'a Self, &'a ItemReplacement)>
+    {
+        let replacements: Vec<_> = replacements.into_iter().collect();
+        let mut seen = HashSet::with_capacity(replacements.len());
+        for (placement, _) in &replacements {
+            if !seen.insert(*placement) {
+                bail!("Room {} {:?} function {} offset {:#x} is replaced more than once", placement.room, placement.script, placement.function, placement.offset);
+            }
+
+            let rdt = rooms.get(&placement.room).ok_or_else(|| anyhow!("Room {} has not been loaded", placement.room))?;
+            if !Self::from_aots(placement.room, &rdt.aots()).contains(placement) {
+                bail!("Room {} has no item placement matching {:?} function {} offset {:#x}", placement.room, placement.script, placement.function, placement.offset);
+            }
+        }
+
+        for (placement, replacement) in replacements {
+            let rdt = rooms.get_mut(&placement.room).ok_or_else(|| anyhow!("Room {} has not been loaded", placement.room))?;
+            placement.replace(rdt, *replacement)?;
+        }
+        Ok(())
+    }
+
+    /// Export a list of placements as CSV with a header row
+    ///
+    /// Positions are raw fixed-point values, and the `item` column is empty for unknown items.
+    pub fn to_csv(placements: &[Self]) -> String {
+        let mut csv = String::new();
+        csv.push_str(Self::CSV_HEADER);
+        csv.push('\n');
+        for placement in placements {
+            let position = placement.position();
+            let item = placement.item().map(|item| csv_field(item.name())).unwrap_or_default();
+            writeln!(
+                csv,
+                "{},{:?},{},{},{},{},{},{},{},{},{},{},{}",
+                placement.room,
+                placement.script,
+                placement.function,
+                placement.offset,
+                placement.aot,
+                placement.item_id,
+                item,
+                placement.quantity,
+                placement.flag,
+                placement.floor,
+                position.x.0,
+                position.z.0,
+                placement.md1,
+            ).unwrap();
+        }
+        csv
+    }
+
+    /// Export a list of placements as a JSON array
+    ///
+    /// Each object has the same fields as the CSV columns, with `item` set to `null` for unknown
+    /// items and the position as a `{"x", "z"}` object.
+    pub fn to_json(placements: &[Self]) -> String {
+        json_array(placements.iter().map(|placement| {
+            let position = placement.position();
+            JsonObject::new()
+                .string("room", &placement.room.to_string())
+                .string("script", &format!("{:?}", placement.script))
+                .field("function", placement.function)
+                .field("offset", placement.offset)
+                .field("aot", placement.aot)
+                .field("item_id", placement.item_id)
+                .optional_string("item", placement.item().map(|item| item.name()))
+                .field("quantity", placement.quantity)
+                .field("flag", placement.flag)
+                .field("floor", placement.floor)
+                .field("position", JsonObject::new().field("x", position.x.0).field("z", position.z.0).finish())
+                .field("md1", placement.md1)
+                .finish()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::re2::{Instruction, RawRdt, RdtSection};
+
+    #[test]
+    fn test_item_placements() {
+        let function = Instruction::assemble("
+            aot_set aot=0 sce=Message sat=0x11
+            item_aot_set aot=1 sce=Item sat=0x31 n_floor=1 x=1000 z=-2000 w=200 h=400 i_item=GreenHerb n_item=1 flag=12 md1=3
+            item_aot_set2 aot=2 sce=Item sat=0x31 i_item=999 n_item=2 flag=13
+            evt_end
+        ").unwrap();
+        let room = RoomId::new(1, 0x0B);
+        let placements = ItemPlacement::from_aots(room, &ScriptAot::collect(ScriptKind::Exec, &[function]));
+        assert_eq!(placements.len(), 2);
+        assert_eq!(placements[0].item(), Some(Item::GreenHerb));
+        assert_eq!((placements[0].aot, placements[0].offset, placements[0].floor), (1, 20, 1));
+        assert_eq!(placements[0].position(), Vec2::new(Fixed32(1100), Fixed32(-1800)));
+        assert_eq!(placements[1].item(), None);
+
+        assert_eq!(ItemPlacement::to_csv(&placements), "\
+room,script,function,offset,aot,item_id,item,quantity,flag,floor,x,z,md1
+20B,Exec,0,20,1,38,Green Herb,1,12,1,1100,-1800,3
+20B,Exec,0,42,2,999,,2,13,0,0,0,0
+");
+
+        let json = ItemPlacement::to_json(&placements);
+        assert!(json.starts_with("[{\"room\":\"20B\",\"script\":\"Exec\",\"function\":0,\"offset\":20,\"aot\":1,\"item_id\":38,\"item\":\"Green Herb\""));
+        assert!(json.ends_with("\"item\":null,\"quantity\":2,\"flag\":13,\"floor\":0,\"position\":{\"x\":0,\"z\":0},\"md1\":0}]"));
+    }
+
+    /// Build an RDT with an init script followed by a section that the parser ignores
+    fn test_rdt(function: Vec<Instruction>) -> RawRdt {
+        let mut script = Rdt::write_script(&[function]).unwrap();
+        // padding after the last instruction that isn't part of any instruction
+        script.extend([0xAB, 0xCD]);
+        RawRdt::with_sections([(RdtSection::InitScript, script), (RdtSection::SpriteId, vec![1, 2, 3, 4])])
+    }
+
+    #[test]
+    fn test_replace_items() {
+        let function = Instruction::assemble("
+            item_aot_set aot=0 sce=Item sat=0x31 i_item=GreenHerb n_item=1 flag=12 md1=3
+            item_aot_set4p aot=1 sce=Item sat=0xb1 i_item=HandgunAmmo n_item=15 flag=13 md1=4
+            evt_end
+        ").unwrap();
+        let raw = test_rdt(function);
+        let original = raw.to_bytes();
+        let room = RoomId::new(0, 0);
+        let mut rooms = BTreeMap::from([(room, Rdt::read(std::io::Cursor::new(&original)).unwrap())]);
+
+        // the padding is kept as an unknown instruction
+        assert_eq!(rooms[&room].script_diagnostics().len(), 1);
+
+        let placements = ItemPlacement::scan(rooms.iter().map(|(room, rdt)| (*room, rdt)));
+        assert_eq!(placements.len(), 2);
+        let replacements = BTreeMap::from([(1usize, ItemReplacement::new(Item::RedHerb, 1, 5))]);
+        let mut changes: Vec<_> = replacements.iter().map(|(i, replacement)| (&placements[*i], replacement)).collect();
+
+        // a stale placement is rejected without changing anything
+        let mut stale = placements[0];
+        stale.item_id = Item::BlueHerb.into();
+        let stale_replacement = ItemReplacement::new(Item::Knife, 1, 0);
+        changes.push((&stale, &stale_replacement));
+        assert!(ItemPlacement::replace_all(&mut rooms, changes.iter().copied()).is_err());
+        changes.pop();
+
+        ItemPlacement::replace_all(&mut rooms, changes).unwrap();
+        let rdt = &rooms[&room];
+        let updated = ItemPlacement::scan([(room, rdt)]);
+        assert_eq!(updated[0], placements[0]);
+        assert_eq!((updated[1].item(), updated[1].quantity, updated[1].md1), (Some(Item::RedHerb), 1, 5));
+        assert_eq!(updated[1].flag, 13);
+
+        let mut written = std::io::Cursor::new(Vec::new());
+        rdt.write(&mut written).unwrap();
+        let written = written.into_inner();
+        assert_eq!(written.len(), original.len());
+        let changed: Vec<_> = (0..written.len()).filter(|&i| written[i] != original[i]).collect();
+        // i_item, n_item, and md1 of the second instruction, after the script's offset table and the
+        // first instruction
+        let i_item = raw.section_offset(RdtSection::InitScript) as usize + 2 + 22 + 22;
+        assert_eq!(changed, vec![i_item, i_item + 2, i_item + 6]);
+    }
+}
@@ -0,0 +1,136 @@
+use binrw::binrw;
+
+use crate::common::*;
+use super::script::Instruction;
+
+/// The number of lights (not counting ambient light) that each camera has
+pub const NUM_LIGHTS: usize = 3;
+
+/// An RGB light color
+#[binrw]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LightColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl LightColor {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// The lighting used for 3D models while a given camera is active
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CameraLights {
+    pub types: [u16; 2],
+    pub colors: [LightColor; NUM_LIGHTS],
+    pub ambient: LightColor,
+    pub positions: [SSVECTOR; NUM_LIGHTS],
+    pub luminosity: [i16; NUM_LIGHTS],
+}
+
+impl CameraLights {
+    /// Set one component of a light's position
+    ///
+    /// `xyz` selects the component: 0 for x, 1 for y, and 2 for z. Returns false if either the light
+    /// index or the component is out of range.
+    pub fn set_position_component(&mut self, index: usize, xyz: u8, value: Fixed16) -> bool {
+        let Some(position) = self.positions.get_mut(index) else {
+            return false;
+        };
+
+        match xyz {
+            0 => position.vx = value,
+            1 => position.vy = value,
+            2 => position.vz = value,
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn set_luminosity(&mut self, index: usize, luminosity: i16) -> bool {
+        let Some(entry) = self.luminosity.get_mut(index) else {
+            return false;
+        };
+
+        *entry = luminosity;
+        true
+    }
+
+    pub fn set_color(&mut self, index: usize, color: LightColor) -> bool {
+        let Some(entry) = self.colors.get_mut(index) else {
+            return false;
+        };
+
+        *entry = color;
+        true
+    }
+
+    /// Apply the effect of a lighting instruction to these lights
+    ///
+    /// This does not check which camera a `*2` instruction is meant for; see
+    /// [`Instruction::light_cut`]. Returns whether the instruction modified the lights.
+    pub fn apply(&mut self, instruction: &Instruction) -> bool {
+        match *instruction {
+            Instruction::LightPosSet { index, xyz, position, .. } => self.set_position_component(index as usize, xyz, position),
+            Instruction::LightPosSet2 { index, xyz, position, .. } => self.set_position_component(index as usize, xyz, Fixed16(position)),
+            Instruction::LightKidoSet { index, luminosity } => self.set_luminosity(index as usize, luminosity),
+            Instruction::LightKidoSet2 { index, luminosity, .. } => self.set_luminosity(index as usize, luminosity as i16),
+            Instruction::LightColorSet { index, r, g, b, .. } | Instruction::LightColorSet2 { index, r, g, b, .. } => {
+                self.set_color(index as usize, LightColor::new(r, g, b))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use binrw::{BinReaderExt, BinWriterExt};
+
+    use super::*;
+
+    fn lights() -> CameraLights {
+        CameraLights {
+            types: [0, 0],
+            colors: [LightColor::new(0xff, 0xe0, 0xc0), LightColor::new(0x40, 0x40, 0x80), LightColor::new(0, 0, 0)],
+            ambient: LightColor::new(0x20, 0x20, 0x20),
+            positions: [
+                SSVECTOR { vx: Fixed16(-3000), vy: Fixed16(-4000), vz: Fixed16(2000) },
+                SSVECTOR { vx: Fixed16(1000), vy: Fixed16(-2000), vz: Fixed16(0) },
+                SSVECTOR { vx: Fixed16(0), vy: Fixed16(0), vz: Fixed16(0) },
+            ],
+            luminosity: [4000, 2500, 0],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let lights = lights();
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(&lights).unwrap();
+        let buf = writer.into_inner();
+        assert_eq!(buf.len(), 0x28);
+        assert_eq!(buf.len(), size_of::<CameraLights>());
+
+        let parsed: CameraLights = Cursor::new(buf).read_le().unwrap();
+        assert_eq!(parsed, lights);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut lights = lights();
+        assert!(lights.apply(&Instruction::LightPosSet { align: 0, index: 1, xyz: 2, position: Fixed16(500) }));
+        assert_eq!(lights.positions[1].vz, Fixed16(500));
+        assert!(lights.apply(&Instruction::LightKidoSet2 { align: 0, n_cut: 3, index: 0, luminosity: 1200 }));
+        assert_eq!(lights.luminosity[0], 1200);
+        assert!(!lights.apply(&Instruction::LightColorSet { index: 5, r: 1, g: 2, b: 3, align: 0 }));
+        assert!(!lights.apply(&Instruction::Nop));
+    }
+}
@@ -0,0 +1,349 @@
+use std::fmt::{Display, Formatter, Write};
+
+use anyhow::{bail, Result};
+
+const CODE_CONTROL_START: u8 = 0xEA;
+const CODE_ITEM_NAME: u8 = 0xF8;
+const CODE_COLOR: u8 = 0xF9;
+const CODE_START: u8 = 0xFA;
+const CODE_YES_NO: u8 = 0xFB;
+const CODE_LINE_BREAK: u8 = 0xFC;
+const CODE_PAUSE: u8 = 0xFD;
+const CODE_END: u8 = 0xFE;
+
+/// Characters of the English font in glyph order
+///
+/// Glyphs that have no obvious Unicode equivalent are `None` and are decoded as
+/// [`MessagePart::Glyph`].
+const ENGLISH_GLYPHS: [Option<char>; 0x55] = [
+    Some(' '), Some('.'), Some('…'), Some('('), Some(')'), None, None, Some('“'),
+    Some('”'), None, Some('0'), Some('1'), Some('2'), Some('3'), Some('4'), Some('5'),
+    Some('6'), Some('7'), Some('8'), Some('9'), Some(':'), None, Some(','), Some('"'),
+    Some('!'), Some('?'), None, Some('A'), Some('B'), Some('C'), Some('D'), Some('E'),
+    Some('F'), Some('G'), Some('H'), Some('I'), Some('J'), Some('K'), Some('L'), Some('M'),
+    Some('N'), Some('O'), Some('P'), Some('Q'), Some('R'), Some('S'), Some('T'), Some('U'),
+    Some('V'), Some('W'), Some('X'), Some('Y'), Some('Z'), Some('['), Some('/'), Some(']'),
+    Some('\''), Some('-'), None, Some('a'), Some('b'), Some('c'), Some('d'), Some('e'),
+    Some('f'), Some('g'), Some('h'), Some('i'), Some('j'), Some('k'), Some('l'), Some('m'),
+    Some('n'), Some('o'), Some('p'), Some('q'), Some('r'), Some('s'), Some('t'), Some('u'),
+    Some('v'), Some('w'), Some('x'), Some('y'), Some('z'),
+];
+
+/// The font a message is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Charset {
+    /// The font used by the English (and other Latin-alphabet) releases
+    English,
+    /// The Japanese font
+    ///
+    /// Japanese glyphs are not mapped to Unicode, so all text decodes to [`MessagePart::Glyph`]s.
+    Japanese,
+}
+
+impl Charset {
+    pub const fn char(&self, glyph: u8) -> Option<char> {
+        match self {
+            Self::English if (glyph as usize) < ENGLISH_GLYPHS.len() => ENGLISH_GLYPHS[glyph as usize],
+            _ => None,
+        }
+    }
+
+    /// Get the font glyph used to display a character, if the font can display it
+    pub fn glyph(&self, c: char) -> Option<u8> {
+        match self {
+            Self::English => ENGLISH_GLYPHS.iter().position(|g| *g == Some(c)).map(|i| i as u8),
+            Self::Japanese => None,
+        }
+    }
+}
+
+/// A piece of a message: either text or a control code
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MessagePart {
+    /// A run of characters
+    Text(String),
+    /// A font glyph with no known character mapping
+    Glyph(u8),
+    /// Start a new line
+    LineBreak,
+    /// Wait for the player to press a button before continuing on a new page
+    Pause(u8),
+    /// Change the text color
+    Color(u8),
+    /// Insert the name of an item
+    ItemName(u8),
+    /// Show a yes/no prompt
+    YesNo(u8),
+    /// Start of message marker
+    Start(u8),
+    /// Any other control code
+    Control { code: u8, arg: u8 },
+}
+
+impl Display for MessagePart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => {
+                for c in text.chars() {
+                    // escape braces so the control code syntax is unambiguous
+                    match c {
+                        '{' => f.write_str("{{")?,
+                        '}' => f.write_str("}}")?,
+                        _ => f.write_char(c)?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Glyph(glyph) => write!(f, "{{glyph {:#04x}}}", glyph),
+            Self::LineBreak => f.write_char('\n'),
+            Self::Pause(arg) => write!(f, "{{pause {}}}", arg),
+            Self::Color(color) => write!(f, "{{color {}}}", color),
+            Self::ItemName(item) => write!(f, "{{item {}}}", item),
+            Self::YesNo(arg) => write!(f, "{{yes_no {}}}", arg),
+            Self::Start(arg) => write!(f, "{{start {}}}", arg),
+            Self::Control { code, arg } => write!(f, "{{control {:#04x} {}}}", code, arg),
+        }
+    }
+}
+
+/// A message section that couldn't be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageDiagnostic {
+    pub charset: Charset,
+    pub reason: String,
+}
+
+impl Display for MessageDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} messages: {}", self.charset, self.reason)
+    }
+}
+
+/// A message displayed in the text box at the bottom of the screen
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Message {
+    pub parts: Vec<MessagePart>,
+    /// Argument of the end-of-message control code
+    pub end: u8,
+}
+
+impl Message {
+    pub const fn new(parts: Vec<MessagePart>) -> Self {
+        Self { parts, end: 0 }
+    }
+
+    /// Decode a message from its encoded bytes
+    ///
+    /// Decoding stops at the end-of-message control code or at the end of the data, whichever comes
+    /// first.
+    pub fn decode(data: &[u8], charset: Charset) -> Self {
+        let mut parts = Vec::new();
+        let mut end = 0;
+        let mut text = String::new();
+        let mut bytes = data.iter().copied();
+
+        while let Some(byte) = bytes.next() {
+            if byte < CODE_CONTROL_START {
+                match charset.char(byte) {
+                    Some(c) => text.push(c),
+                    None => {
+                        Self::flush_text(&mut parts, &mut text);
+                        parts.push(MessagePart::Glyph(byte));
+                    }
+                }
+                continue;
+            }
+
+            Self::flush_text(&mut parts, &mut text);
+            if byte == CODE_LINE_BREAK {
+                parts.push(MessagePart::LineBreak);
+                continue;
+            }
+
+            let arg = bytes.next().unwrap_or(0);
+            parts.push(match byte {
+                CODE_END => {
+                    end = arg;
+                    break;
+                }
+                CODE_ITEM_NAME => MessagePart::ItemName(arg),
+                CODE_COLOR => MessagePart::Color(arg),
+                CODE_START => MessagePart::Start(arg),
+                CODE_YES_NO => MessagePart::YesNo(arg),
+                CODE_PAUSE => MessagePart::Pause(arg),
+                code => MessagePart::Control { code, arg },
+            });
+        }
+
+        Self::flush_text(&mut parts, &mut text);
+        Self { parts, end }
+    }
+
+    /// Read a message section into a list of messages
+    ///
+    /// The section begins with a table of 16-bit offsets to each message, relative to the start of
+    /// the section. The number of messages is implied by the offset of the first message.
+    pub fn read_table(data: &[u8], charset: Charset) -> Result<Vec<Self>> {
+        if data.len() < 2 {
+            return Ok(Vec::new());
+        }
+
+        let read_offset = |i: usize| u16::from_le_bytes([data[i * 2], data[i * 2 + 1]]) as usize;
+        let num_messages = read_offset(0) / 2;
+        if num_messages * 2 > data.len() {
+            bail!("Message offset table is larger than the message section");
+        }
+
+        let mut messages = Vec::with_capacity(num_messages);
+        for i in 0..num_messages {
+            let offset = read_offset(i);
+            if offset > data.len() {
+                bail!("Message {} offset {:#x} is outside the message section", i, offset);
+            }
+
+            messages.push(Self::decode(&data[offset..], charset));
+        }
+
+        Ok(messages)
+    }
+
+    /// Encode this message into the game's format, including the end-of-message control code
+    ///
+    /// Fails if the message contains a character the font cannot display.
+    pub fn encode(&self, charset: Charset) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for part in &self.parts {
+            match part {
+                MessagePart::Text(text) => {
+                    for c in text.chars() {
+                        let Some(glyph) = charset.glyph(c) else {
+                            bail!("Character {:?} cannot be displayed in the {:?} font", c, charset);
+                        };
+                        data.push(glyph);
+                    }
+                }
+                MessagePart::Glyph(glyph) => {
+                    if *glyph >= CODE_CONTROL_START {
+                        bail!("Glyph {:#04x} is a control code", glyph);
+                    }
+                    data.push(*glyph);
+                }
+                MessagePart::LineBreak => data.push(CODE_LINE_BREAK),
+                MessagePart::Pause(arg) => data.extend_from_slice(&[CODE_PAUSE, *arg]),
+                MessagePart::Color(arg) => data.extend_from_slice(&[CODE_COLOR, *arg]),
+                MessagePart::ItemName(arg) => data.extend_from_slice(&[CODE_ITEM_NAME, *arg]),
+                MessagePart::YesNo(arg) => data.extend_from_slice(&[CODE_YES_NO, *arg]),
+                MessagePart::Start(arg) => data.extend_from_slice(&[CODE_START, *arg]),
+                MessagePart::Control { code, arg } => {
+                    if *code < CODE_CONTROL_START || matches!(*code, CODE_LINE_BREAK | CODE_END) {
+                        bail!("{:#04x} is not a control code with an argument", code);
+                    }
+                    data.extend_from_slice(&[*code, *arg]);
+                }
+            }
+        }
+
+        data.extend_from_slice(&[CODE_END, self.end]);
+        Ok(data)
+    }
+
+    /// Encode a list of messages into a message section, including the offset table
+    pub fn write_table(messages: &[Self], charset: Charset) -> Result<Vec<u8>> {
+        let mut offsets = Vec::with_capacity(messages.len());
+        let mut body = Vec::new();
+        let table_size = messages.len() * 2;
+        for (i, message) in messages.iter().enumerate() {
+            let offset = table_size + body.len();
+            let Ok(offset) = u16::try_from(offset) else {
+                bail!("Message {} offset {:#x} does not fit in the offset table", i, offset);
+            };
+            offsets.push(offset);
+            body.extend(message.encode(charset)?);
+        }
+
+        let mut data = Vec::with_capacity(table_size + body.len());
+        for offset in offsets {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend(body);
+        Ok(data)
+    }
+
+    fn flush_text(parts: &mut Vec<MessagePart>, text: &mut String) {
+        if !text.is_empty() {
+            parts.push(MessagePart::Text(std::mem::take(text)));
+        }
+    }
+
+    /// The message's text with control codes removed and line breaks converted to newlines
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for part in &self.parts {
+            match part {
+                MessagePart::Text(s) => text.push_str(s),
+                MessagePart::LineBreak => text.push('\n'),
+                _ => (),
+            }
+        }
+        text
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for part in &self.parts {
+            write!(f, "{}", part)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // "{start 0}Hi.{color 1}OK?{yes_no 0}" then end
+        let data = [0xFA, 0x00, 0x22, 0x43, 0x01, 0xF9, 0x01, 0x29, 0x25, 0x19, 0xFC, 0x05, 0xFB, 0x00, 0xFE, 0x03, 0x1B];
+        let message = Message::decode(&data, Charset::English);
+        assert_eq!(message.parts, vec![
+            MessagePart::Start(0),
+            MessagePart::Text(String::from("Hi.")),
+            MessagePart::Color(1),
+            MessagePart::Text(String::from("OK?")),
+            MessagePart::LineBreak,
+            MessagePart::Glyph(0x05),
+            MessagePart::YesNo(0),
+        ]);
+        assert_eq!(message.end, 3);
+        assert_eq!(message.text(), "Hi.OK?\n");
+        assert_eq!(message.to_string(), "{start 0}Hi.{color 1}OK?\n{glyph 0x05}{yes_no 0}");
+    }
+
+    #[test]
+    fn test_read_table() {
+        let data = [0x04, 0x00, 0x07, 0x00, 0x1B, 0xFE, 0x00, 0x1C, 0xFE, 0x00];
+        let messages = Message::read_table(&data, Charset::English).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].text(), "A");
+        assert_eq!(messages[1].text(), "B");
+    }
+
+    #[test]
+    fn test_encode() {
+        let data = [0xFA, 0x00, 0x22, 0x43, 0x01, 0xF9, 0x01, 0x29, 0x25, 0x19, 0xFC, 0x05, 0xFB, 0x00, 0xFE, 0x03];
+        let message = Message::decode(&data, Charset::English);
+        assert_eq!(message.encode(Charset::English).unwrap(), data);
+
+        let message = Message::new(vec![MessagePart::Text(String::from("Caf\u{e9}"))]);
+        assert!(message.encode(Charset::English).is_err());
+    }
+
+    #[test]
+    fn test_write_table() {
+        let data = [0x04, 0x00, 0x07, 0x00, 0x1B, 0xFE, 0x00, 0x1C, 0xFE, 0x00];
+        let messages = Message::read_table(&data, Charset::English).unwrap();
+        assert_eq!(Message::write_table(&messages, Charset::English).unwrap(), data);
+    }
+}
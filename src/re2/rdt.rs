@@ -1,12 +1,22 @@
-use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Context, Result};
-use binrw::{binrw, BinReaderExt, BinWriterExt};
+use binrw::{binrw, BinReaderExt, BinWriterExt, VecArgs};
 use enum_map::{Enum, EnumMap, enum_map};
 
 use crate::common::*;
 use super::animation::AnimationSet;
-use super::script::Instruction;
+use super::aot::ScriptAot;
+use super::camera::{Camera, CameraZone};
+use super::collision::Collision;
+use super::light::CameraLights;
+use super::message::{Charset, Message, MessageDiagnostic};
+use super::script::{Instruction, InstructionInfo, Interpreter, ScriptDiagnostic, ScriptIssue, ScriptKind, ScriptListing};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
 pub enum RdtSection {
@@ -95,7 +105,7 @@ impl RdtSection {
 }
 
 #[binrw]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct RdtHeader {
     n_sprite: u8,
     n_cut: u8,
@@ -222,18 +232,100 @@ impl RawRdt {
 
         let mut writer = Cursor::new(Vec::new());
         writer.write_le(&offsets)?;
-        let mut buf = writer.into_inner();
-        // if there was any extra data in the buffer, leave it there
-        let old_data = &self.sections[RdtSection::Model];
-        if old_data.len() > buf.len() {
-            buf.extend_from_slice(&old_data[buf.len()..]);
+        let old_size = self.header.o_model as usize * size_of::<ModelOffsets>();
+        self.replace_section_prefix(RdtSection::Model, old_size, writer.into_inner())?;
+        self.header.o_model = offsets.len() as u8;
+        Ok(())
+    }
+
+    pub const fn num_cuts(&self) -> usize {
+        self.header.n_cut as usize
+    }
+
+    /// The number of AOTs the room has, which script AOT indexes must be less than
+    pub const fn num_aots(&self) -> usize {
+        self.header.n_room_at as usize
+    }
+
+    pub fn cameras(&self) -> Result<Vec<Camera>> {
+        let Some(mut reader) = self.reader(RdtSection::CameraPos) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(reader.read_le_args(VecArgs { count: self.num_cuts(), inner: () })?)
+    }
+
+    /// Replace the room's cameras
+    ///
+    /// Each camera has an entry in the light section, so if the room has lights, the number of
+    /// cameras can only be changed with [`RawRdt::set_cameras_and_lights`].
+    pub fn set_cameras(&mut self, cameras: Vec<Camera>) -> Result<()> {
+        if cameras.len() != self.num_cuts() && self.header.offset(RdtSection::Light) != 0 {
+            bail!("Changing the number of cameras from {} to {} requires new lights", self.num_cuts(), cameras.len());
         }
 
-        self.replace_section(RdtSection::Model, buf)?;
-        self.header.o_model = offsets.len() as u8;
+        self.write_cameras(cameras)
+    }
+
+    fn write_cameras(&mut self, cameras: Vec<Camera>) -> Result<()> {
+        if cameras.len() > u8::MAX as usize {
+            bail!("Too many cameras");
+        }
+
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(&cameras)?;
+        let old_size = self.num_cuts() * size_of::<Camera>();
+        self.replace_section_prefix(RdtSection::CameraPos, old_size, writer.into_inner())?;
+        self.header.n_cut = cameras.len() as u8;
         Ok(())
     }
 
+    pub fn lights(&self) -> Result<Vec<CameraLights>> {
+        let Some(mut reader) = self.reader(RdtSection::Light) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(reader.read_le_args(VecArgs { count: self.num_cuts(), inner: () })?)
+    }
+
+    pub fn set_lights(&mut self, lights: &[CameraLights]) -> Result<()> {
+        if lights.len() != self.num_cuts() {
+            bail!("Expected lights for {} cameras but got {}", self.num_cuts(), lights.len());
+        }
+
+        self.write_lights(lights)
+    }
+
+    /// Write the light section, which must currently have an entry for each camera
+    fn write_lights(&mut self, lights: &[CameraLights]) -> Result<()> {
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(&lights)?;
+        let old_size = self.num_cuts() * size_of::<CameraLights>();
+        self.replace_section_prefix(RdtSection::Light, old_size, writer.into_inner())
+    }
+
+    /// Replace the room's cameras along with the lights for each of them, which may change the
+    /// number of cameras
+    pub fn set_cameras_and_lights(&mut self, cameras: Vec<Camera>, lights: &[CameraLights]) -> Result<()> {
+        if lights.len() != cameras.len() {
+            bail!("Expected lights for {} cameras but got {}", cameras.len(), lights.len());
+        }
+        if cameras.len() > u8::MAX as usize {
+            bail!("Too many cameras");
+        }
+
+        // the light section is replaced first because its old size depends on the old camera count
+        self.write_lights(lights)?;
+        self.write_cameras(cameras)
+    }
+
+    pub fn camera_zones(&self) -> Result<Vec<CameraZone>> {
+        match self.reader(RdtSection::CameraZone) {
+            Some(reader) => CameraZone::read_list(reader),
+            None => Ok(Vec::new()),
+        }
+    }
+
     fn shift(&mut self, offset: u32, delta: i32) -> Result<()> {
         if delta == 0 {
             return Ok(());
@@ -260,7 +352,30 @@ impl RawRdt {
                 model_offset.md1_offset = model_offset.md1_offset.checked_add_signed(delta).ok_or_else(|| anyhow!("Overflow while updating model offsets"))?;
             }
         }
-        self.set_model_offsets(model_offsets)
+        self.set_model_offsets(model_offsets)?;
+
+        // camera mask pointers are also file offsets
+        let mut cameras = self.cameras()?;
+        if cameras.iter().any(|camera| camera.mask_offset >= offset) {
+            for camera in &mut cameras {
+                if camera.has_mask() && camera.mask_offset >= offset {
+                    camera.mask_offset = camera.mask_offset.checked_add_signed(delta).ok_or_else(|| anyhow!("Overflow while updating camera mask offsets"))?;
+                }
+            }
+            self.set_cameras(cameras)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the first `old_size` bytes of a section, keeping any data after them
+    fn replace_section_prefix(&mut self, section: RdtSection, old_size: usize, mut data: Vec<u8>) -> Result<()> {
+        let old_data = &self.sections[section];
+        if old_data.len() > old_size {
+            data.extend_from_slice(&old_data[old_size..]);
+        }
+
+        self.replace_section(section, data)
     }
 
     pub fn replace_section(&mut self, section: RdtSection, data: Vec<u8>) -> Result<()> {
@@ -289,6 +404,16 @@ impl RawRdt {
         Ok(())
     }
 
+    /// Overwrite part of a section without changing its size
+    pub fn patch_section(&mut self, section: RdtSection, offset: usize, data: &[u8]) -> Result<()> {
+        let section_data = &mut self.sections[section];
+        let Some(target) = offset.checked_add(data.len()).and_then(|end| section_data.get_mut(offset..end)) else {
+            bail!("Patch of {} bytes at {:#x} does not fit in section {:?} of {} bytes", data.len(), offset, section, section_data.len());
+        };
+        target.copy_from_slice(data);
+        Ok(())
+    }
+
     pub fn size(&self) -> usize {
         size_of::<RdtHeader>() + self.sections.values().map(Vec::len).sum::<usize>()
     }
@@ -297,6 +422,11 @@ impl RawRdt {
         self.sections[section].len()
     }
 
+    /// The offset of a section from the start of the file, or 0 if the RDT doesn't have it
+    pub const fn section_offset(&self, section: RdtSection) -> u32 {
+        self.header.offset(section)
+    }
+
     pub fn read<T: Read + Seek>(mut f: T) -> Result<Self> {
         use RdtSection::*;
 
@@ -405,6 +535,30 @@ impl RawRdt {
     }
 }
 
+#[cfg(test)]
+impl RawRdt {
+    /// Build an RDT with the given sections laid out in order after the header
+    pub(crate) fn with_sections<I: IntoIterator<Item = (RdtSection, Vec<u8>)>>(sections: I) -> Self {
+        let mut rdt = Self {
+            header: RdtHeader::default(),
+            sections: EnumMap::default(),
+            section_order: Vec::new(),
+        };
+        for (section, data) in sections {
+            rdt.header.set_offset(section, rdt.size() as u32);
+            rdt.section_order.push(section);
+            rdt.sections[section] = data;
+        }
+        rdt
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write(&mut writer).unwrap();
+        writer.into_inner()
+    }
+}
+
 #[binrw]
 #[derive(Debug)]
 pub struct ModelOffsets {
@@ -412,95 +566,169 @@ pub struct ModelOffsets {
     pub md1_offset: u32,
 }
 
+/// A rectangular area of the room's floor
+///
+/// Floors determine the footstep sound played when walking over them and the height level of the
+/// ground.
 #[binrw]
-#[derive(Debug)]
-pub struct Collider {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Floor {
     pub x: Fixed16,
     pub z: Fixed16,
-    pub w: UFixed16,
-    pub h: UFixed16,
-    pub collision_mask: u16,
-    pub quadrant_mask: u16,
-    pub floor: u32,
+    pub width: UFixed16,
+    pub height: UFixed16,
+    pub sound_type: u16,
+    pub level: u16,
 }
 
-impl Collider {
-    pub const fn collision_mask(&self) -> u16 {
-        self.collision_mask & 0xfff0
+impl Floor {
+    pub fn contains(&self, point: Vec2) -> bool {
+        let min = Vec2::new(self.x, self.z);
+        let max = min + (self.width.to_32(), self.height.to_32());
+        point.x >= min.x && point.x <= max.x && point.z >= min.z && point.z <= max.z
     }
 }
 
 #[binrw]
-#[derive(Debug)]
-pub struct Collision {
-    pub cell_x: Fixed16,
-    pub cell_z: Fixed16,
-    count: u32,
-    pub ceiling: i32,
-    pub dummy: u32,
-    #[br(count = count - 1)]
-    pub colliders: Vec<Collider>,
+#[derive(Debug, Clone, Default)]
+struct FloorData {
+    #[br(temp)]
+    #[bw(calc = floors.len() as u16)]
+    num_floors: u16,
+    #[br(count = num_floors)]
+    pub floors: Vec<Floor>,
+    unknown: u16,
 }
 
-impl Default for Collision {
-    fn default() -> Self {
-        Self {
-            cell_x: Fixed16(0),
-            cell_z: Fixed16(0),
-            count: 0,
-            ceiling: 0,
-            dummy: 0,
-            colliders: Vec::new(),
+/// Identifies a room by its stage and room number
+///
+/// `stage` is zero-based, as in door `next_stage` fields, but rooms are written the way they
+/// appear in RDT file names, with a one-based stage followed by two hex digits for the room, so
+/// stage 0 room 0x0A is `10A`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RoomId {
+    pub stage: u8,
+    pub room: u8,
+}
+
+impl RoomId {
+    pub const fn new(stage: u8, room: u8) -> Self {
+        Self { stage, room }
+    }
+
+    /// Parse an RDT file name like `ROOM1000.RDT` into its room and player number
+    ///
+    /// The player number is 0 for Leon's scenario and 1 for Claire's.
+    pub fn from_file_name(name: &str) -> Option<(Self, u8)> {
+        let upper = name.to_ascii_uppercase();
+        let code = upper.strip_prefix("ROOM")?.strip_suffix(".RDT")?;
+        if code.len() != 4 {
+            return None;
         }
+
+        let room = code.get(..3)?.parse().ok()?;
+        let player = code.get(3..)?.parse().ok()?;
+        Some((room, player))
+    }
+
+    /// The name of this room's RDT file for a player
+    pub fn file_name(&self, player: u8) -> String {
+        format!("ROOM{}{}.RDT", self, player)
     }
 }
 
-#[binrw]
-#[derive(Debug)]
-pub struct Floor {
-    pub x: Fixed16,
-    pub z: Fixed16,
-    pub width: UFixed16,
-    pub height: UFixed16,
-    pub unknown: u16,
-    pub level: u16,
+impl Display for RoomId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:X}{:02X}", self.stage as u16 + 1, self.room)
+    }
 }
 
-#[binrw]
-#[derive(Debug)]
-struct FloorData {
-    num_floors: u16,
-    #[br(count = num_floors)]
-    pub floors: Vec<Floor>,
-    unknown: u16,
+impl FromStr for RoomId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (Some(stage), Some(room)) = (s.get(..1), s.get(1..)) else {
+            bail!("Room {:?} should be a stage digit followed by a two-digit hex room number", s);
+        };
+        let stage = u8::from_str_radix(stage, 16).ok().and_then(|stage| stage.checked_sub(1));
+        let room = u8::from_str_radix(room, 16).ok().filter(|_| room.len() == 2);
+        match (stage, room) {
+            (Some(stage), Some(room)) => Ok(Self { stage, room }),
+            _ => bail!("Room {:?} should be a stage digit followed by a two-digit hex room number", s),
+        }
+    }
 }
 
 /// A parsed representation of an RDT file
 ///
 /// An RDT file defines a room in the game. This parsed RDT representation does not currently
-/// contain the entire contents of the RDT. Only collision, floors, and scripts are currently
-/// supported.
+/// contain the entire contents of the RDT. Only cameras, camera zones, lights, messages, collision,
+/// floors, and scripts are currently supported.
 #[derive(Debug)]
 pub struct Rdt {
     raw: RawRdt,
+    cameras: Vec<Camera>,
+    camera_zones: Vec<CameraZone>,
+    lights: Vec<CameraLights>,
+    jp_messages: Vec<Message>,
+    messages: Vec<Message>,
+    message_diagnostics: Vec<MessageDiagnostic>,
     collision: Collision,
-    floors: Vec<Floor>,
+    floor_data: FloorData,
     init_script: Vec<Vec<Instruction>>,
     exec_script: Vec<Vec<Instruction>>,
+    script_diagnostics: Vec<ScriptDiagnostic>,
     animation_sets: Vec<AnimationSet>,
 }
 
 impl Rdt {
-    fn read_function(script_size: u64, reader: &mut Cursor<Vec<u8>>) -> Vec<Instruction> {
+    /// Read the rest of a function as an [`Instruction::Unknown`] and record why
+    fn read_unknown(
+        reader: &mut Cursor<Vec<u8>>,
+        end: u64,
+        diagnostic: ScriptDiagnostic,
+        diagnostics: &mut Vec<ScriptDiagnostic>,
+    ) -> Result<Instruction> {
+        let mut bytes = vec![0u8; (end - reader.position()) as usize];
+        reader.read_exact(&mut bytes)?;
+        diagnostics.push(diagnostic);
+        Ok(Instruction::Unknown { opcode: bytes[0], bytes: bytes.split_off(1) })
+    }
+
+    /// Read a function that ends at the given position in the reader
+    ///
+    /// If an instruction can't be decoded, either because its opcode is unknown or because it would
+    /// run past the end of the function, the rest of the function is kept as an
+    /// [`Instruction::Unknown`] and the problem is added to `diagnostics`. The same goes for any
+    /// bytes between the function's final `EvtEnd` and the end of the function.
+    fn read_function(
+        kind: ScriptKind,
+        index: usize,
+        end: u64,
+        reader: &mut Cursor<Vec<u8>>,
+        diagnostics: &mut Vec<ScriptDiagnostic>,
+    ) -> Result<Vec<Instruction>> {
         let mut script = Vec::new();
+        let start = reader.position();
 
         let mut nesting = 0u32;
-        while reader.position() < script_size {
-            let inst = match reader.read_le::<Instruction>() {
-                Ok(inst) => inst,
-                Err(_) => {
-                    // we probably just overran the end of the function, so just stop parsing and
-                    // return what we have
+        while reader.position() < end {
+            let position = reader.position();
+            let opcode = reader.get_ref()[position as usize];
+            let remaining = (end - position) as usize;
+            let problem = match InstructionInfo::for_opcode(opcode) {
+                None => Some(format!("unknown opcode {:#04x}", opcode)),
+                Some(info) if info.size() > remaining => {
+                    Some(format!("{} needs {} bytes but only {} remain in the function", info.mnemonic, info.size(), remaining))
+                }
+                Some(_) => None,
+            };
+
+            let inst = match problem {
+                None => reader.read_le::<Instruction>()?,
+                Some(reason) => {
+                    let diagnostic = ScriptDiagnostic { script: kind, function: index, offset: (position - start) as usize, reason };
+                    script.push(Self::read_unknown(reader, end, diagnostic, diagnostics)?);
                     break;
                 }
             };
@@ -514,74 +742,144 @@ impl Rdt {
 
             script.push(inst);
             // the size calculation may not be reliable, so if we see the end-of-function
-            // instruction, we'll go ahead and bail
+            // instruction, we'll go ahead and bail, keeping anything left over so that it isn't lost
+            // when the script is written back
             if is_evt_end && nesting == 0 {
+                let position = reader.position();
+                if position < end {
+                    let reason = format!("{} bytes after the end of the function", end - position);
+                    let diagnostic = ScriptDiagnostic { script: kind, function: index, offset: (position - start) as usize, reason };
+                    script.push(Self::read_unknown(reader, end, diagnostic, diagnostics)?);
+                }
                 break;
             }
         }
 
-        script
+        Ok(script)
+    }
+
+    fn parse_script(buf: Vec<u8>, kind: ScriptKind) -> Result<(Vec<Vec<Instruction>>, Vec<ScriptDiagnostic>)> {
+        let script_size = buf.len();
+        let mut reader = Cursor::new(buf);
+
+        let offset: u16 = reader.read_le()?;
+        let num_functions = (offset >> 1) as usize;
+
+        let mut offsets = Vec::with_capacity(num_functions + 1);
+        offsets.push(offset as u64);
+        while offsets.len() < num_functions {
+            let offset = reader.read_le::<u16>()? as u64;
+            offsets.push(offset);
+        }
+        offsets.push(script_size as u64);
+
+        let mut script = Vec::with_capacity(num_functions);
+        let mut diagnostics = Vec::new();
+        for (i, pair) in offsets.windows(2).enumerate() {
+            let offset = pair[0];
+            let next_offset = pair[1].min(script_size as u64);
+            if offset > next_offset {
+                let reason = format!("function starts at {:#x}, after the next function at {:#x}", offset, next_offset);
+                diagnostics.push(ScriptDiagnostic { script: kind, function: i, offset: 0, reason });
+                script.push(Vec::new());
+                continue;
+            }
+
+            reader.seek(SeekFrom::Start(offset))?;
+
+            script.push(Self::read_function(kind, i, next_offset, &mut reader, &mut diagnostics)?);
+        }
+
+        Ok((script, diagnostics))
     }
 
-    fn read_script(raw: &RawRdt, section: RdtSection) -> Result<Vec<Vec<Instruction>>> {
+    fn read_script(raw: &RawRdt, section: RdtSection, kind: ScriptKind) -> Result<(Vec<Vec<Instruction>>, Vec<ScriptDiagnostic>)> {
         Ok(if let Some(mut reader) = raw.reader(section) {
             let script_size = raw.section_size(section);
 
             if script_size == 0 {
-                Vec::new()
+                (Vec::new(), Vec::new())
             } else {
                 let mut buf = vec![0u8; script_size];
                 reader.read_exact(&mut buf)?;
 
-                let mut reader = Cursor::new(buf);
-
-                let offset: u16 = reader.read_le()?;
-                let num_functions = (offset >> 1) as usize;
-
-                let mut offsets = Vec::with_capacity(num_functions + 1);
-                offsets.push(offset as u64);
-                while offsets.len() < num_functions {
-                    let offset = reader.read_le::<u16>()? as u64;
-                    offsets.push(offset);
-                }
-                offsets.push(script_size as u64);
+                Self::parse_script(buf, kind)?
+            }
+        } else {
+            (Vec::new(), Vec::new())
+        })
+    }
 
-                let mut script = Vec::with_capacity(num_functions);
-                for pair in offsets.windows(2) {
-                    let offset = pair[0];
-                    let next_offset = pair[1];
+    /// Serialize a script into the format expected by `read_script`
+    ///
+    /// The script begins with a table of 16-bit offsets to each function, relative to the start of
+    /// the script. The functions follow immediately after the table.
+    pub(crate) fn write_script(script: &[Vec<Instruction>]) -> Result<Vec<u8>> {
+        if script.is_empty() {
+            return Ok(Vec::new());
+        }
 
-                    reader.seek(SeekFrom::Start(offset))?;
+        let table_size = script.len() * size_of::<u16>();
+        let mut writer = Cursor::new(Vec::new());
+        let mut offsets = Vec::with_capacity(script.len());
+        for (i, function) in script.iter().enumerate() {
+            let offset = table_size as u64 + writer.position();
+            let Ok(offset) = u16::try_from(offset) else {
+                bail!("Function {} offset {:#x} does not fit in the script's offset table", i, offset);
+            };
+            offsets.push(offset);
+            writer.write_le(function)?;
+        }
 
-                    script.push(Self::read_function(next_offset, &mut reader));
-                }
+        let mut table = Cursor::new(Vec::with_capacity(table_size + writer.get_ref().len()));
+        table.write_le(&offsets)?;
+        let mut buf = table.into_inner();
+        buf.extend(writer.into_inner());
+        Ok(buf)
+    }
 
-                script
+    /// Read a message section, leaving it undecoded if it's malformed so that the rest of the room
+    /// can still be read
+    fn read_messages(raw: &RawRdt, section: RdtSection, charset: Charset, diagnostics: &mut Vec<MessageDiagnostic>) -> Vec<Message> {
+        match Message::read_table(raw.section(section), charset) {
+            Ok(messages) => messages,
+            Err(e) => {
+                diagnostics.push(MessageDiagnostic { charset, reason: e.to_string() });
+                Vec::new()
             }
-        } else {
-            Vec::new()
-        })
+        }
     }
 
     pub fn read<T: Read + Seek>(f: T) -> Result<Self> {
         let raw = RawRdt::read(f)?;
 
+        let cameras = raw.cameras().context("RDT cameras")?;
+
+        let camera_zones = raw.camera_zones().context("RDT camera zones")?;
+
+        let lights = raw.lights().context("RDT lights")?;
+
+        let mut message_diagnostics = Vec::new();
+        let jp_messages = Self::read_messages(&raw, RdtSection::JpMessage, Charset::Japanese, &mut message_diagnostics);
+
+        let messages = Self::read_messages(&raw, RdtSection::OtherMessage, Charset::English, &mut message_diagnostics);
+
         let collision = if let Some(mut collision_reader) = raw.reader(RdtSection::Collision) {
             collision_reader.read_le().context("RDT collision")?
         } else {
             Collision::default()
         };
 
-        let floors = if let Some(mut floor_reader) = raw.reader(RdtSection::Floor) {
-            let floor_data: FloorData = floor_reader.read_le().context("RDT floor data")?;
-            floor_data.floors
+        let floor_data = if let Some(mut floor_reader) = raw.reader(RdtSection::Floor) {
+            floor_reader.read_le().context("RDT floor data")?
         } else {
-            Vec::new()
+            FloorData::default()
         };
 
-        let init_script = Self::read_script(&raw, RdtSection::InitScript)?;
+        let (init_script, mut script_diagnostics) = Self::read_script(&raw, RdtSection::InitScript, ScriptKind::Init)?;
 
-        let exec_script = Self::read_script(&raw, RdtSection::ExecScript)?;
+        let (exec_script, exec_diagnostics) = Self::read_script(&raw, RdtSection::ExecScript, ScriptKind::Exec)?;
+        script_diagnostics.extend(exec_diagnostics);
 
         let animation_sets = if let Some(animation_reader) = raw.reader(RdtSection::Animation) {
             AnimationSet::read_rdt(animation_reader).context("RDT animation")?
@@ -591,24 +889,196 @@ impl Rdt {
 
         Ok(Self {
             raw,
+            cameras,
+            camera_zones,
+            lights,
+            jp_messages,
+            messages,
+            message_diagnostics,
             collision,
-            floors,
+            floor_data,
             init_script,
             exec_script,
+            script_diagnostics,
             animation_sets,
         })
     }
 
+    /// Read every RDT for a player's scenario from a directory
+    ///
+    /// Files are matched by name, as described on [`RoomId::from_file_name`], and anything else in
+    /// the directory is ignored.
+    pub fn read_scenario<P: AsRef<Path>>(dir: P, player: u8) -> Result<BTreeMap<RoomId, Self>> {
+        let dir = dir.as_ref();
+        let mut rooms = BTreeMap::new();
+        for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read directory {}", dir.display()))? {
+            let path = entry?.path();
+            let Some((room, file_player)) = path.file_name().and_then(|name| name.to_str()).and_then(RoomId::from_file_name) else {
+                continue;
+            };
+            if file_player != player {
+                continue;
+            }
+
+            let file = File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+            let rdt = Self::read(BufReader::new(file)).with_context(|| format!("Failed to read {}", path.display()))?;
+            rooms.insert(room, rdt);
+        }
+
+        Ok(rooms)
+    }
+
     pub fn center(&self) -> Vec2 {
         Vec2::new(self.collision.cell_x, self.collision.cell_z)
     }
 
+    pub fn cameras(&self) -> &[Camera] {
+        &self.cameras
+    }
+
+    pub fn camera_zones(&self) -> &[CameraZone] {
+        &self.camera_zones
+    }
+
+    /// Get the camera that will be active when a character on the given floor moves to the given
+    /// position while the given camera is active
+    ///
+    /// This is the camera that [`Rdt::camera_switch`] switches to, or the current camera if no zone
+    /// switches away from it.
+    pub fn camera_at(&self, current_cut: u8, floor: u8, pos: Vec2) -> u8 {
+        self.camera_switch(current_cut, floor, pos).unwrap_or(current_cut)
+    }
+
+    /// Get the camera the game would switch to when a character on the given floor moves to the
+    /// given position while the given camera is active
+    ///
+    /// Returns `None` if the camera would not change.
+    pub fn camera_switch(&self, current_cut: u8, floor: u8, pos: Vec2) -> Option<u8> {
+        self.camera_zones
+            .iter()
+            .filter(|zone| zone.from_cut == current_cut && zone.to_cut != current_cut && zone.applies_to_floor(floor))
+            .find(|zone| zone.contains(pos))
+            .map(|zone| zone.to_cut)
+    }
+
+    pub fn lights(&self) -> &[CameraLights] {
+        &self.lights
+    }
+
+    pub fn set_lights(&mut self, lights: Vec<CameraLights>) -> Result<()> {
+        self.raw.set_lights(&lights)?;
+        self.lights = lights;
+        Ok(())
+    }
+
+    /// Apply the effect of a lighting instruction to the room's lights
+    ///
+    /// The `*2` variants of the lighting instructions name the camera they modify; the others
+    /// modify the current camera. Returns whether the instruction was a lighting instruction.
+    pub fn apply_light_instruction(&mut self, current_cut: u8, instruction: &Instruction) -> Result<bool> {
+        if !instruction.is_light() {
+            return Ok(false);
+        }
+
+        let cut = instruction.light_cut().unwrap_or(current_cut) as usize;
+        let mut lights = self.lights.clone();
+        let Some(cut_lights) = lights.get_mut(cut) else {
+            bail!("Camera {} has no lights", cut);
+        };
+
+        if !cut_lights.apply(instruction) {
+            return Ok(false);
+        }
+
+        self.set_lights(lights)?;
+        Ok(true)
+    }
+
+    /// Messages from the Japanese message section
+    pub fn jp_messages(&self) -> &[Message] {
+        &self.jp_messages
+    }
+
+    /// Messages from the non-Japanese message section
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub fn set_jp_messages(&mut self, messages: Vec<Message>) -> Result<()> {
+        let data = Message::write_table(&messages, Charset::Japanese)?;
+        self.raw.replace_section(RdtSection::JpMessage, data)?;
+        self.jp_messages = messages;
+        self.message_diagnostics.retain(|diagnostic| diagnostic.charset != Charset::Japanese);
+        Ok(())
+    }
+
+    pub fn set_messages(&mut self, messages: Vec<Message>) -> Result<()> {
+        let data = Message::write_table(&messages, Charset::English)?;
+        self.raw.replace_section(RdtSection::OtherMessage, data)?;
+        self.messages = messages;
+        self.message_diagnostics.retain(|diagnostic| diagnostic.charset != Charset::English);
+        Ok(())
+    }
+
+    /// Message sections that couldn't be decoded
+    ///
+    /// A section with a problem has no messages but is otherwise left as it was, so it's written
+    /// back unchanged unless its messages are replaced.
+    pub fn message_diagnostics(&self) -> &[MessageDiagnostic] {
+        &self.message_diagnostics
+    }
+
+    /// Look up a message by the index used in the `MessageOn` instruction
+    pub fn message(&self, index: usize) -> Option<&Message> {
+        self.messages.get(index)
+    }
+
     pub fn collision(&self) -> &Collision {
         &self.collision
     }
 
+    /// Replace the room's collision data
+    ///
+    /// Like cameras and lights, any data in the section after the collider table is kept.
+    pub fn set_collision(&mut self, collision: Collision) -> Result<()> {
+        let old_size = self.collision.to_bytes()?.len();
+        self.raw.replace_section_prefix(RdtSection::Collision, old_size, collision.to_bytes()?)?;
+        self.collision = collision;
+        Ok(())
+    }
+
+    /// Modify the room's collision data and store the result back into the RDT
+    pub fn edit_collision<F: FnOnce(&mut Collision)>(&mut self, f: F) -> Result<()> {
+        let mut collision = self.collision.clone();
+        f(&mut collision);
+        self.set_collision(collision)
+    }
+
     pub fn floors(&self) -> &[Floor] {
-        &self.floors
+        &self.floor_data.floors
+    }
+
+    /// Replace the room's floors
+    ///
+    /// The value following the floor list is preserved, as is any data in the section after it.
+    pub fn set_floors(&mut self, floors: Vec<Floor>) -> Result<()> {
+        if floors.len() > u16::MAX as usize {
+            bail!("Too many floors");
+        }
+
+        let mut old_data = Cursor::new(Vec::new());
+        old_data.write_le(&self.floor_data)?;
+        let floor_data = FloorData { floors, unknown: self.floor_data.unknown };
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(&floor_data)?;
+        self.raw.replace_section_prefix(RdtSection::Floor, old_data.get_ref().len(), writer.into_inner())?;
+        self.floor_data = floor_data;
+        Ok(())
+    }
+
+    /// Get the first floor containing the given point
+    pub fn floor_at(&self, pos: Vec2) -> Option<&Floor> {
+        self.floor_data.floors.iter().find(|floor| floor.contains(pos))
     }
 
     pub fn init_script(&self) -> impl Iterator<Item = &[Instruction]> {
@@ -619,6 +1089,105 @@ impl Rdt {
         self.exec_script.iter().map(|x| x.as_slice())
     }
 
+    /// Replace the room's init script
+    pub fn set_init_script(&mut self, script: Vec<Vec<Instruction>>) -> Result<()> {
+        self.raw.replace_section(RdtSection::InitScript, Self::write_script(&script)?)?;
+        self.init_script = script;
+        self.script_diagnostics.retain(|diagnostic| diagnostic.script != ScriptKind::Init);
+        Ok(())
+    }
+
+    /// Replace the room's exec script
+    pub fn set_exec_script(&mut self, script: Vec<Vec<Instruction>>) -> Result<()> {
+        self.raw.replace_section(RdtSection::ExecScript, Self::write_script(&script)?)?;
+        self.exec_script = script;
+        self.script_diagnostics.retain(|diagnostic| diagnostic.script != ScriptKind::Exec);
+        Ok(())
+    }
+
+    /// Get the instruction at a byte offset in a script function
+    pub fn instruction_at(&self, script: ScriptKind, function: usize, offset: usize) -> Option<&Instruction> {
+        let functions = match script {
+            ScriptKind::Init => &self.init_script,
+            ScriptKind::Exec => &self.exec_script,
+        };
+        Self::index_at(functions.get(function)?, offset).map(|index| &functions[function][index])
+    }
+
+    fn index_at(function: &[Instruction], offset: usize) -> Option<usize> {
+        let mut position = 0usize;
+        for (i, instruction) in function.iter().enumerate() {
+            if position == offset {
+                return Some(i);
+            }
+            position += instruction.size();
+        }
+        None
+    }
+
+    /// Overwrite an instruction in one of the room's scripts with one of the same size
+    ///
+    /// Unlike [`Rdt::set_init_script`] and [`Rdt::set_exec_script`], which rebuild the whole
+    /// script section, this only writes over the original instruction's bytes, so everything else in
+    /// the RDT stays byte-for-byte the same, including any bytes the parsed script doesn't account
+    /// for.
+    pub fn patch_instruction(&mut self, script: ScriptKind, function: usize, offset: usize, instruction: Instruction) -> Result<()> {
+        let (section, functions) = match script {
+            ScriptKind::Init => (RdtSection::InitScript, &mut self.init_script),
+            ScriptKind::Exec => (RdtSection::ExecScript, &mut self.exec_script),
+        };
+        let Some(index) = functions.get(function).and_then(|instructions| Self::index_at(instructions, offset)) else {
+            bail!("No instruction starts at offset {:#x} of {:?} function {}", offset, script, function);
+        };
+        let old_size = functions[function][index].size();
+        if instruction.size() != old_size {
+            bail!("{} is {} bytes but the instruction it replaces is {} bytes", instruction.info().mnemonic, instruction.size(), old_size);
+        }
+
+        // function offsets come from the section's table so that the patch lands where the function
+        // really is, even if the script has data the parser skipped
+        let data = self.raw.section(section);
+        let table_entry = function * size_of::<u16>();
+        let Some(entry) = data.get(table_entry..table_entry + size_of::<u16>()) else {
+            bail!("{:?} function {} is missing from the script's offset table", script, function);
+        };
+        let start = u16::from_le_bytes([entry[0], entry[1]]) as usize + offset;
+
+        let mut writer = Cursor::new(Vec::with_capacity(old_size));
+        writer.write_le(&instruction)?;
+        self.raw.patch_section(section, start, writer.get_ref())?;
+        functions[function][index] = instruction;
+        Ok(())
+    }
+
+    /// Write the room's RDT file
+    pub fn write<T: Write + Seek>(&self, f: T) -> Result<()> {
+        self.raw.write(f)
+    }
+
+    /// Get every AOT set or reset by the room's init and exec scripts, in script order
+    pub fn aots(&self) -> Vec<ScriptAot> {
+        let mut aots = ScriptAot::collect(ScriptKind::Init, &self.init_script);
+        aots.extend(ScriptAot::collect(ScriptKind::Exec, &self.exec_script));
+        aots
+    }
+
+    /// Check the room's scripts for problems that would break them in game
+    pub fn validate(&self) -> Vec<ScriptIssue> {
+        let num_aots = self.raw.num_aots();
+        let mut issues = ScriptIssue::check_script(ScriptKind::Init, &self.init_script, num_aots);
+        issues.extend(ScriptIssue::check_script(ScriptKind::Exec, &self.exec_script, num_aots));
+        issues
+    }
+
+    /// Problems encountered while decoding the room's scripts
+    ///
+    /// Functions with problems still contain every byte of the original function, with anything
+    /// that couldn't be decoded kept as an [`Instruction::Unknown`].
+    pub fn script_diagnostics(&self) -> &[ScriptDiagnostic] {
+        &self.script_diagnostics
+    }
+
     pub fn animation_sets(&self) -> &[AnimationSet] {
         &self.animation_sets
     }
@@ -627,27 +1196,288 @@ impl Rdt {
         self.raw.section(section)
     }
 
-    pub fn print_scripts(&self) {
-        println!("Init script:");
-        for instruction in &self.init_script {
-            println!("\t{:?}", instruction);
-        }
+    /// Get a disassembly listing of the room's scripts
+    ///
+    /// The listing implements `Display`, so it can be written to any `fmt::Write` or `io::Write`.
+    pub fn script_listing(&self) -> ScriptListing<'_> {
+        ScriptListing::new(&self.init_script, &self.exec_script)
+    }
 
-        for (i, function) in self.exec_script.iter().enumerate() {
-            println!("\nExec function {}:", i);
-            for instruction in function.as_slice() {
-                println!("\t{:?}", instruction);
-            }
-        }
+    /// Create an interpreter to simulate this room's scripts
+    pub fn interpreter(&self) -> Interpreter<'_> {
+        Interpreter::new(&self.init_script, &self.exec_script)
+    }
+
+    pub fn print_scripts(&self) {
+        print!("{}", self.script_listing());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::re2::Collider;
+
+    #[test]
+    fn test_room_id() {
+        let room = RoomId::new(0, 0x0A);
+        assert_eq!(room.to_string(), "10A");
+        assert_eq!("10A".parse::<RoomId>().unwrap(), room);
+        assert_eq!("71F".parse::<RoomId>().unwrap(), RoomId::new(6, 0x1F));
+        assert!("00A".parse::<RoomId>().is_err());
+        assert!("1A".parse::<RoomId>().is_err());
+
+        assert_eq!(RoomId::from_file_name("room10a1.rdt"), Some((room, 1)));
+        assert_eq!(RoomId::from_file_name("ROOM10A.RDT"), None);
+        assert_eq!(room.file_name(0), "ROOM10A0.RDT");
+    }
+
+    #[test]
+    fn test_floor_data() {
+        let floor_data = FloorData {
+            floors: vec![Floor {
+                x: Fixed16(-500),
+                z: Fixed16(1000),
+                width: UFixed16(2000),
+                height: UFixed16(1500),
+                sound_type: 3,
+                level: 0,
+            }],
+            unknown: 0x1234,
+        };
+
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_le(&floor_data).unwrap();
+        let buf = writer.into_inner();
+        assert_eq!(buf.len(), 2 + 12 + 2);
+        assert_eq!(&buf[..2], &[1, 0]);
+
+        let parsed: FloorData = Cursor::new(&buf).read_le().unwrap();
+        assert_eq!(parsed.floors, floor_data.floors);
+        assert_eq!(parsed.unknown, 0x1234);
+
+        let floor = &parsed.floors[0];
+        assert!(floor.contains(Vec2::new(0, 2000)));
+        assert!(!floor.contains(Vec2::new(0, 999)));
+
+        // data after the floors is kept when they're replaced
+        let mut section = buf;
+        section.extend([0xEE; 4]);
+        let raw = RawRdt::with_sections([(RdtSection::Floor, section), (RdtSection::Block, vec![1, 2, 3, 4])]);
+        let mut rdt = Rdt::read(Cursor::new(raw.to_bytes())).unwrap();
+        let floors = vec![floor_data.floors[0].clone(), floor_data.floors[0].clone()];
+        rdt.set_floors(floors.clone()).unwrap();
+
+        let reread = Rdt::read(Cursor::new(rdt.raw.to_bytes())).unwrap();
+        assert_eq!(reread.floors(), floors.as_slice());
+        assert_eq!(&reread.raw(RdtSection::Floor)[2 + 12 * 2..], &[0x34, 0x12, 0xEE, 0xEE, 0xEE, 0xEE]);
+        assert_eq!(reread.raw(RdtSection::Block), &[1, 2, 3, 4]);
+    }
 
     #[test]
-    fn test_size() {
-        assert_eq!(size_of::<Collider>(), 0x10);
+    fn test_set_collision_keeps_trailing_data() {
+        let mut section = Collision::new(Fixed16(-1000), Fixed16(2000), -1800).to_bytes().unwrap();
+        section.extend([0xEE; 4]);
+        let raw = RawRdt::with_sections([(RdtSection::Collision, section), (RdtSection::Floor, vec![0, 0, 0, 0])]);
+        let mut rdt = Rdt::read(Cursor::new(raw.to_bytes())).unwrap();
+
+        rdt.edit_collision(|collision| {
+            collision.add_collider(Collider {
+                x: Fixed16(100),
+                z: Fixed16(200),
+                w: UFixed16(300),
+                h: UFixed16(400),
+                collision_mask: 0x0010,
+                quadrant_mask: 0,
+                floor: 0,
+            });
+        }).unwrap();
+
+        let data = rdt.raw(RdtSection::Collision);
+        assert_eq!(data.len(), 0x10 + 0x10 + 4);
+        assert_eq!(&data[0x20..], &[0xEE; 4]);
+        assert_eq!(rdt.raw.section_offset(RdtSection::Floor) as usize, rdt.raw.section_offset(RdtSection::Collision) as usize + data.len());
+
+        let reread = Rdt::read(Cursor::new(rdt.raw.to_bytes())).unwrap();
+        assert_eq!(reread.collision(), rdt.collision());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_camera_at() {
+        let zone = |floor, from_cut, to_cut, x0: i16, x1: i16| CameraZone {
+            flags: 0,
+            floor,
+            from_cut,
+            to_cut,
+            x0: Fixed16(x0),
+            z0: Fixed16(0),
+            x1: Fixed16(x1),
+            z1: Fixed16(0),
+            x2: Fixed16(x1),
+            z2: Fixed16(1000),
+            x3: Fixed16(x0),
+            z3: Fixed16(1000),
+        };
+        // from camera 0 or 1, stepping into x 1000..2000 on floor 0 switches to camera 2
+        let zones = [zone(0, 0, 2, 1000, 2000), zone(0, 1, 2, 1000, 2000), zone(crate::re2::ALL_FLOORS, 2, 0, 0, 1000)];
+        let mut writer = Cursor::new(Vec::new());
+        CameraZone::write_list(&zones, &mut writer).unwrap();
+        let raw = RawRdt::with_sections([(RdtSection::CameraZone, writer.into_inner())]);
+        let rdt = Rdt::read(Cursor::new(raw.to_bytes())).unwrap();
+
+        let pos = Vec2::new(1500, 500);
+        assert_eq!(rdt.camera_at(0, 0, pos), 2);
+        assert_eq!(rdt.camera_at(1, 0, pos), 2);
+        // wrong floor
+        assert_eq!(rdt.camera_at(0, 1, pos), 0);
+        assert_eq!(rdt.camera_at(2, 1, Vec2::new(500, 500)), 0);
+        assert_eq!(rdt.camera_at(2, 1, pos), 2);
+    }
+
+    #[test]
+    fn test_set_cameras_and_lights() {
+        let camera = |mask_offset| Camera {
+            flags: 0,
+            projection_distance: 0x2ee,
+            eye: Vec3::new(-1200, -2050, 8400),
+            target: Vec3::new(-900, -1800, 1200),
+            mask_offset,
+        };
+        let light = |luminosity| {
+            let mut lights: CameraLights = Cursor::new([0u8; size_of::<CameraLights>()]).read_le().unwrap();
+            lights.luminosity = [luminosity; 3];
+            lights
+        };
+
+        let mut cameras = Cursor::new(Vec::new());
+        cameras.write_le(&camera(0)).unwrap();
+        let mut lights = Cursor::new(Vec::new());
+        lights.write_le(&light(100)).unwrap();
+        let mut raw = RawRdt::with_sections([
+            (RdtSection::CameraPos, cameras.into_inner()),
+            (RdtSection::Light, lights.into_inner()),
+            (RdtSection::SpriteId, vec![1, 2, 3, 4]),
+        ]);
+        raw.header.n_cut = 1;
+        assert!(raw.set_cameras(vec![camera(0), camera(0)]).is_err());
+        assert!(raw.set_cameras_and_lights(vec![camera(0), camera(0)], &[light(100)]).is_err());
+
+        raw.set_cameras_and_lights(vec![camera(0), camera(0)], &[light(100), light(200)]).unwrap();
+        let reread = RawRdt::read(Cursor::new(raw.to_bytes())).unwrap();
+        assert_eq!(reread.cameras().unwrap(), vec![camera(0), camera(0)]);
+        assert_eq!(reread.lights().unwrap(), vec![light(100), light(200)]);
+        assert_eq!(reread.section(RdtSection::SpriteId), &[1, 2, 3, 4]);
+
+        // same number of cameras
+        raw.set_cameras(vec![camera(0), camera(0)]).unwrap();
+    }
+
+    #[test]
+    fn test_bad_message_table() {
+        // the offset table claims 8 messages but the section only has room for one offset
+        let raw = RawRdt::with_sections([(RdtSection::OtherMessage, vec![0x10, 0]), (RdtSection::InitScript, vec![2, 0, 1, 0])]);
+        let original = raw.to_bytes();
+        let rdt = Rdt::read(Cursor::new(&original)).unwrap();
+
+        assert!(rdt.messages().is_empty());
+        assert_eq!(rdt.message_diagnostics().len(), 1);
+        assert_eq!(rdt.message_diagnostics()[0].charset, Charset::English);
+        assert_eq!(rdt.init_script().count(), 1);
+        assert_eq!(rdt.raw.to_bytes(), original);
+    }
+
+    #[test]
+    fn test_apply_light_instruction() {
+        let mut rdt = Rdt::read(Cursor::new(RawRdt::with_sections([]).to_bytes())).unwrap();
+        assert!(!rdt.apply_light_instruction(5, &Instruction::Nop).unwrap());
+        assert!(rdt.apply_light_instruction(5, &Instruction::LightKidoSet { index: 0, luminosity: 100 }).is_err());
+    }
+
+    #[test]
+    fn test_script_round_trip() {
+        let script = vec![
+            vec![
+                Instruction::IfElCk { align: 0, size: 12 },
+                Instruction::Ck { flag: 1, id: 0x2a, on_off: 1 },
+                Instruction::Set { flag: 1, id: 0x2b, on_off: 1 },
+                Instruction::EndIf(0),
+                Instruction::EvtEnd(0),
+            ],
+            vec![Instruction::Sleeping(30), Instruction::EvtEnd(0)],
+        ];
+
+        assert_eq!(ScriptIssue::check_script(ScriptKind::Init, &script, 0), Vec::new());
+
+        let buf = Rdt::write_script(&script).unwrap();
+        assert_eq!(&buf[..4], &[4, 0, 20, 0]);
+        assert_eq!(Rdt::parse_script(buf, ScriptKind::Init).unwrap(), (script, Vec::new()));
+    }
+
+    #[test]
+    fn test_script_trailing_bytes() {
+        let raw = RawRdt::with_sections([
+            (RdtSection::InitScript, vec![4, 0, 11, 0, 0x0A, 30, 0, 0x01, 0, 0xAB, 0xCD, 0x01, 0]),
+            (RdtSection::SpriteId, vec![1, 2, 3, 4]),
+        ]);
+        let original = raw.to_bytes();
+        let mut rdt = Rdt::read(Cursor::new(&original)).unwrap();
+        assert_eq!(rdt.script_diagnostics().len(), 1);
+        assert_eq!(rdt.script_diagnostics()[0].function, 0);
+
+        // rebuilding the script from what was parsed gives back the original bytes
+        rdt.set_init_script(rdt.init_script.clone()).unwrap();
+        assert_eq!(rdt.raw.to_bytes(), original);
+    }
+
+    #[test]
+    fn test_set_scripts() {
+        let init = vec![vec![Instruction::Sleeping(30), Instruction::EvtEnd(0)]];
+        let exec = vec![vec![Instruction::Nop, Instruction::EvtEnd(0)]];
+        let raw = RawRdt::with_sections([
+            (RdtSection::InitScript, Rdt::write_script(&init).unwrap()),
+            (RdtSection::ExecScript, Rdt::write_script(&exec).unwrap()),
+            (RdtSection::SpriteId, vec![1, 2, 3, 4]),
+        ]);
+        let mut rdt = Rdt::read(Cursor::new(raw.to_bytes())).unwrap();
+        assert_eq!(rdt.init_script().collect::<Vec<_>>(), vec![init[0].as_slice()]);
+
+        let new_init = vec![
+            vec![Instruction::Set { flag: 1, id: 0x2b, on_off: 1 }, Instruction::EvtEnd(0)],
+            vec![Instruction::Sleeping(30), Instruction::EvtEnd(0)],
+        ];
+        rdt.set_init_script(new_init.clone()).unwrap();
+
+        let reread = Rdt::read(Cursor::new(rdt.raw.to_bytes())).unwrap();
+        assert_eq!(reread.init_script, new_init);
+        assert_eq!(reread.exec_script, exec);
+        assert_eq!(reread.raw(RdtSection::InitScript), Rdt::write_script(&new_init).unwrap());
+        assert_eq!(reread.raw(RdtSection::SpriteId), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_script_unknown_opcode() {
+        let script = vec![vec![Instruction::Sleeping(30), Instruction::Unknown { opcode: 0xA0, bytes: vec![1, 2, 3] }]];
+        let buf = Rdt::write_script(&script).unwrap();
+
+        let (parsed, diagnostics) = Rdt::parse_script(buf.clone(), ScriptKind::Exec).unwrap();
+        assert_eq!(parsed, script);
+        assert_eq!(diagnostics, vec![ScriptDiagnostic {
+            script: ScriptKind::Exec,
+            function: 0,
+            offset: 3,
+            reason: String::from("unknown opcode 0xa0"),
+        }]);
+        assert_eq!(Rdt::write_script(&parsed).unwrap(), buf);
+
+        // bytes after the function's EvtEnd
+        let (parsed, diagnostics) = Rdt::parse_script(vec![2, 0, 0x01, 0, 0xAB, 0xCD], ScriptKind::Init).unwrap();
+        assert_eq!(parsed, vec![vec![Instruction::EvtEnd(0), Instruction::Unknown { opcode: 0xAB, bytes: vec![0xCD] }]]);
+        assert_eq!(diagnostics[0].offset, 2);
+        assert_eq!(diagnostics[0].reason, "2 bytes after the end of the function");
+
+        // an instruction cut off by the end of the function
+        let (parsed, diagnostics) = Rdt::parse_script(vec![2, 0, 0x0A, 30], ScriptKind::Init).unwrap();
+        assert_eq!(parsed, vec![vec![Instruction::Unknown { opcode: 0x0A, bytes: vec![30] }]]);
+        assert_eq!(diagnostics[0].reason, "sleeping needs 3 bytes but only 2 remain in the function");
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::aot::{Aot, AotChange, DoorAot, Item, ScriptAot};
+use super::export::{dot_escape, json_array, json_string, JsonObject};
+use super::rdt::{Rdt, RoomId};
+use super::script::ScriptKind;
+
+/// The lock on a door
+///
+/// `key_type` is the item that unlocks the door and `key_id` identifies the lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DoorKey {
+    pub key_id: u8,
+    pub key_type: u8,
+}
+
+impl DoorKey {
+    /// The item that unlocks the door, if it's a known item
+    pub fn item(&self) -> Option<Item> {
+        Item::try_from(self.key_type as u16).ok()
+    }
+}
+
+/// A door from one room to another, as set up by a `DoorAotSet` or `DoorAotSet4p` instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoorEdge {
+    pub from: RoomId,
+    pub to: RoomId,
+    /// The door's AOT index in the room it's in
+    pub aot: u8,
+    pub script: ScriptKind,
+    pub function: usize,
+    /// Byte offset of the instruction from the start of the function
+    pub offset: usize,
+    pub door: DoorAot,
+}
+
+impl DoorEdge {
+    /// The lock on the door, or `None` if both `key_id` and `key_type` are 0
+    pub const fn key(&self) -> Option<DoorKey> {
+        if self.door.key_id == 0 && self.door.key_type == 0 {
+            None
+        } else {
+            Some(DoorKey { key_id: self.door.key_id, key_type: self.door.key_type })
+        }
+    }
+}
+
+/// A graph of rooms connected by doors
+///
+/// Rooms are nodes and each door AOT in a room's scripts is an edge to the room it leads to. A room
+/// that sets up the same door more than once, such as in different branches of a script, has an
+/// edge for each.
+#[derive(Debug, Clone, Default)]
+pub struct RoomGraph {
+    rooms: BTreeSet<RoomId>,
+    doors: Vec<DoorEdge>,
+}
+
+impl RoomGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a graph from a set of rooms
+    pub fn build<'a, I: IntoIterator<Item = (RoomId, &'a Rdt)>>(rooms: I) -> Self {
+        let mut graph = Self::new();
+        for (room, rdt) in rooms {
+            graph.add_room(room, rdt);
+        }
+        graph
+    }
+
+    /// Build a graph from every RDT for a player's scenario in a directory
+    ///
+    /// See [`Rdt::read_scenario`] for how files are found.
+    pub fn load<P: AsRef<Path>>(dir: P, player: u8) -> Result<Self> {
+        let rooms = Rdt::read_scenario(dir, player)?;
+        Ok(Self::build(rooms.iter().map(|(room, rdt)| (*room, rdt))))
+    }
+
+    /// Add a room and the doors its scripts set up
+    pub fn add_room(&mut self, room: RoomId, rdt: &Rdt) {
+        self.add_doors(room, &rdt.aots());
+    }
+
+    /// Add a room and the doors among a list of its AOTs
+    pub fn add_doors(&mut self, room: RoomId, aots: &[ScriptAot]) {
+        self.rooms.insert(room);
+        for aot in aots {
+            if let AotChange::Set(Aot::Door { base, door }) = aot.change {
+                self.doors.push(DoorEdge {
+                    from: room,
+                    to: RoomId::new(door.next_stage, door.next_room),
+                    aot: base.id,
+                    script: aot.script,
+                    function: aot.function,
+                    offset: aot.offset,
+                    door,
+                });
+            }
+        }
+    }
+
+    /// The rooms that have been added to the graph
+    pub const fn rooms(&self) -> &BTreeSet<RoomId> {
+        &self.rooms
+    }
+
+    pub fn doors(&self) -> &[DoorEdge] {
+        &self.doors
+    }
+
+    pub fn doors_from(&self, room: RoomId) -> impl Iterator<Item = &DoorEdge> {
+        self.doors.iter().filter(move |door| door.from == room)
+    }
+
+    pub fn doors_to(&self, room: RoomId) -> impl Iterator<Item = &DoorEdge> {
+        self.doors.iter().filter(move |door| door.to == room)
+    }
+
+    /// Doors that lead to rooms that aren't in the graph
+    ///
+    /// When the graph has every room in a scenario, these are doors that warp somewhere that
+    /// doesn't exist.
+    pub fn dangling_doors(&self) -> impl Iterator<Item = &DoorEdge> {
+        self.doors.iter().filter(|door| !self.rooms.contains(&door.to))
+    }
+
+    /// The rooms that can be reached from a room through any door, including the room itself
+    pub fn reachable_from(&self, start: RoomId) -> BTreeSet<RoomId> {
+        let mut neighbors: BTreeMap<RoomId, Vec<RoomId>> = BTreeMap::new();
+        for door in &self.doors {
+            neighbors.entry(door.from).or_default().push(door.to);
+        }
+
+        let mut reachable = BTreeSet::from([start]);
+        let mut queue = vec![start];
+        while let Some(room) = queue.pop() {
+            for &next in neighbors.get(&room).into_iter().flatten() {
+                if reachable.insert(next) {
+                    queue.push(next);
+                }
+            }
+        }
+        reachable
+    }
+
+    fn key_label(key: &DoorKey) -> String {
+        match key.item() {
+            Some(item) => format!("{} (key {:#04x})", item.name(), key.key_id),
+            None => format!("key type {:#04x} (key {:#04x})", key.key_type, key.key_id),
+        }
+    }
+
+    /// Export the graph in Graphviz DOT format
+    ///
+    /// Locked doors are labeled with their key, and rooms that doors lead to but that aren't in the
+    /// graph are drawn dashed.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", dot_escape(name)).unwrap();
+        writeln!(dot, "    node [shape=box];").unwrap();
+        for room in &self.rooms {
+            writeln!(dot, "    \"{}\";", room).unwrap();
+        }
+        let missing: BTreeSet<_> = self.dangling_doors().map(|door| door.to).collect();
+        for room in missing {
+            writeln!(dot, "    \"{}\" [style=dashed];", room).unwrap();
+        }
+
+        for door in &self.doors {
+            match door.key() {
+                Some(key) => writeln!(dot, "    \"{}\" -> \"{}\" [label=\"{}\"];", door.from, door.to, dot_escape(&Self::key_label(&key))).unwrap(),
+                None => writeln!(dot, "    \"{}\" -> \"{}\";", door.from, door.to).unwrap(),
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export the graph as JSON
+    ///
+    /// The object has a `rooms` array of room names and a `doors` array with each door's rooms,
+    /// location in the scripts, destination, and key. Positions and angles are raw fixed-point
+    /// values.
+    pub fn to_json(&self) -> String {
+        let rooms = json_array(self.rooms.iter().map(|room| json_string(&room.to_string())));
+        let doors = json_array(self.doors.iter().map(|door| {
+            let key = door.key().map_or_else(|| "null".to_string(), |key| {
+                JsonObject::new()
+                    .field("key_id", key.key_id)
+                    .field("key_type", key.key_type)
+                    .optional_string("item", key.item().map(|item| item.name()))
+                    .finish()
+            });
+            let d = &door.door;
+            let next_pos = JsonObject::new()
+                .field("x", d.next_pos.x.0)
+                .field("y", d.next_pos.y.0)
+                .field("z", d.next_pos.z.0)
+                .finish();
+            JsonObject::new()
+                .string("from", &door.from.to_string())
+                .string("to", &door.to.to_string())
+                .field("aot", door.aot)
+                .string("script", &format!("{:?}", door.script))
+                .field("function", door.function)
+                .field("offset", door.offset)
+                .field("next_pos", next_pos)
+                .field("next_dir_y", d.next_dir_y.0)
+                .field("next_cut", d.next_cut)
+                .field("next_floor", d.next_floor)
+                .field("door_type", d.door_type)
+                .field("key", key)
+                .finish()
+        }));
+        JsonObject::new().field("rooms", rooms).field("doors", doors).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::re2::Instruction;
+
+    fn room_aots(source: &str) -> Vec<ScriptAot> {
+        ScriptAot::collect(ScriptKind::Init, &[Instruction::assemble(source).unwrap()])
+    }
+
+    #[test]
+    fn test_room_graph() {
+        let hall = RoomId::new(0, 0);
+        let office = RoomId::new(0, 1);
+        let mut graph = RoomGraph::new();
+        graph.add_doors(hall, &room_aots("
+            door_aot_set aot=0 sce=Door sat=0x31 next_stage=0 next_room=1 next_cut=2 next_pos_x=100
+            door_aot_set aot=1 sce=Door sat=0x31 next_stage=1 next_room=5 key_id=3 key_type=0xfe
+            evt_end
+        "));
+        graph.add_doors(office, &room_aots("
+            door_aot_set aot=0 sce=Door sat=0x31 next_stage=0 next_room=0
+            evt_end
+        "));
+
+        assert_eq!(graph.doors().len(), 3);
+        let locked: Vec<_> = graph.doors_from(hall).filter_map(|door| door.key()).collect();
+        assert_eq!(locked, vec![DoorKey { key_id: 3, key_type: 0xfe }]);
+        assert_eq!(graph.doors_to(hall).count(), 1);
+
+        let dangling: Vec<_> = graph.dangling_doors().map(|door| door.to).collect();
+        assert_eq!(dangling, vec![RoomId::new(1, 5)]);
+        assert_eq!(graph.reachable_from(office), BTreeSet::from([hall, office, RoomId::new(1, 5)]));
+
+        let dot = graph.to_dot("Leon");
+        assert!(dot.contains("    \"100\" -> \"101\";\n"));
+        assert!(dot.contains("    \"205\" [style=dashed];\n"));
+        assert!(dot.contains("\"100\" -> \"205\" [label=\"key type 0xfe (key 0x03)\"]"));
+
+        let json = graph.to_json();
+        assert!(json.starts_with("{\"rooms\":[\"100\",\"101\"],\"doors\":[{\"from\":\"100\",\"to\":\"101\",\"aot\":0,\"script\":\"Init\""));
+        assert!(json.contains("\"next_pos\":{\"x\":100,\"y\":0,\"z\":0},\"next_dir_y\":0,\"next_cut\":2"));
+        assert!(json.contains("\"key\":{\"key_id\":3,\"key_type\":254,\"item\":null}"));
+        assert!(json.ends_with("\"key\":null}]}"));
+    }
+}
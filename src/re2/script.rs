@@ -1,12 +1,174 @@
-use binrw::binrw;
+use std::fmt::{Display, Formatter};
+use std::io::Cursor;
+
+use anyhow::{bail, Result};
+use binrw::{binrw, BinReaderExt, BinWriterExt};
 
 use crate::common::*;
 
+mod asm;
+
+mod cfg;
+pub use cfg::*;
+
+mod decompile;
+pub use decompile::*;
+
+mod disasm;
+pub use disasm::*;
+
+mod edit;
+pub use edit::*;
+
+mod interp;
+pub use interp::*;
+
+mod ops;
+pub use ops::*;
+
+mod symbols;
+pub use symbols::*;
+
+mod tables;
+use tables::*;
+
+mod validate;
+pub use validate::*;
+
 pub const MAX_OPCODE: u8 = 0x8E;
 pub const NUM_INSTRUCTIONS: usize = 0x8F;
 
+/// The encoded type of an instruction field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    U8,
+    I8,
+    U16,
+    I16,
+    Fixed16,
+    UFixed16,
+}
+
+impl FieldType {
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 | Self::Fixed16 | Self::UFixed16 => 2,
+        }
+    }
+
+    pub const fn is_signed(&self) -> bool {
+        matches!(self, Self::I8 | Self::I16 | Self::Fixed16)
+    }
+
+    /// The range of values that fit in this field
+    ///
+    /// Both signed and unsigned interpretations are accepted so that, for example, -1 can be
+    /// written to an unsigned field.
+    pub const fn range(&self) -> (i32, i32) {
+        match self.size() {
+            1 => (i8::MIN as i32, u8::MAX as i32),
+            _ => (i16::MIN as i32, u16::MAX as i32),
+        }
+    }
+
+    fn read(&self, bytes: &[u8]) -> i32 {
+        match self {
+            Self::U8 => bytes[0] as i32,
+            Self::I8 => bytes[0] as i8 as i32,
+            Self::U16 | Self::UFixed16 => u16::from_le_bytes([bytes[0], bytes[1]]) as i32,
+            Self::I16 | Self::Fixed16 => i16::from_le_bytes([bytes[0], bytes[1]]) as i32,
+        }
+    }
+}
+
+/// A named field in an instruction's encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Field {
+    pub name: &'static str,
+    pub type_: FieldType,
+}
+
+impl Field {
+    pub const fn new(name: &'static str, type_: FieldType) -> Self {
+        Self { name, type_ }
+    }
+
+    /// Whether this field is padding with no meaning
+    pub fn is_align(&self) -> bool {
+        self.name == "align"
+    }
+}
+
+/// Static information about an instruction: its names and the fields that follow its opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstructionInfo {
+    /// The name of the instruction's variant in [`Instruction`]
+    pub name: &'static str,
+    /// The lowercase name used in assembly listings
+    pub mnemonic: &'static str,
+    pub fields: &'static [Field],
+}
+
+impl InstructionInfo {
+    pub const fn new(name: &'static str, mnemonic: &'static str, fields: &'static [Field]) -> Self {
+        Self { name, mnemonic, fields }
+    }
+
+    /// The encoded size of the instruction in bytes, including the opcode
+    pub const fn size(&self) -> usize {
+        let mut size = 1;
+        let mut i = 0;
+        while i < self.fields.len() {
+            size += self.fields[i].type_.size();
+            i += 1;
+        }
+        size
+    }
+
+    pub fn field(&self, name: &str) -> Option<(usize, &'static Field)> {
+        self.fields.iter().enumerate().find(|(_, field)| field.name == name)
+    }
+
+    pub const fn for_opcode(opcode: u8) -> Option<&'static Self> {
+        if opcode > MAX_OPCODE {
+            None
+        } else {
+            Some(&INSTRUCTION_INFO[opcode as usize])
+        }
+    }
+
+    /// Look up an instruction by its mnemonic, returning its opcode and info
+    pub fn for_mnemonic(mnemonic: &str) -> Option<(u8, &'static Self)> {
+        INSTRUCTION_INFO.iter().enumerate().find(|(_, info)| info.mnemonic == mnemonic).map(|(i, info)| (i as u8, info))
+    }
+}
+
+/// Which of a room's two scripts a function belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptKind {
+    Init,
+    Exec,
+}
+
+/// A problem encountered while decoding a script function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptDiagnostic {
+    pub script: ScriptKind,
+    pub function: usize,
+    /// Byte offset of the problem from the start of the function
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl Display for ScriptDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} function {} at {:#06x}: {}", self.script, self.function, self.offset, self.reason)
+    }
+}
+
 #[binrw]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instruction {
     #[brw(magic = 0x00u8)]
     Nop,
@@ -584,6 +746,16 @@ pub enum Instruction {
         timer1: u16,
         data16: u16,
     },
+    /// An instruction that couldn't be decoded, kept as raw bytes so it can be written back out
+    ///
+    /// This is never produced by reading an `Instruction` directly; script readers fall back to it
+    /// when decoding fails.
+    #[br(pre_assert(false))]
+    Unknown {
+        opcode: u8,
+        #[br(count = 0)]
+        bytes: Vec<u8>,
+    },
 }
 
 impl Instruction {
@@ -595,13 +767,145 @@ impl Instruction {
         matches!(self, Self::EndIf(_) | Self::Next(_) | Self::EdWhile(_) | Self::EWhile(_) | Self::ESwitch(_))
     }
 
+    /// Check whether this instruction closes a block opened by the given instruction
+    pub const fn closes(&self, opener: &Self) -> bool {
+        matches!(
+            (opener, self),
+            (Self::IfElCk { .. }, Self::EndIf(_))
+                | (Self::For { .. } | Self::For2 { .. }, Self::Next(_))
+                | (Self::While { .. }, Self::EWhile(_))
+                | (Self::Do { .. }, Self::EdWhile(_))
+                | (Self::Switch { .. }, Self::ESwitch(_))
+        )
+    }
+
+    /// The distance in bytes from the start of this instruction to the location it branches to
+    ///
+    /// - `IfElCk`: where execution continues when the condition fails: the first instruction of the
+    ///   else branch (after the `ElseCk`) if there is one, otherwise the `EndIf`.
+    /// - `ElseCk`: the `EndIf` at the end of the else branch.
+    /// - `For`, `While`, `Do`, and `Switch`: the instruction after the closing `Next`, `EWhile`,
+    ///   `EdWhile`, or `ESwitch`.
+    /// - `Case`: the next `Case`, `Default`, or `ESwitch`.
+    /// - `Goto`: the jump destination.
+    pub const fn branch_offset(&self) -> Option<i32> {
+        Some(match self {
+            Self::IfElCk { size, .. } | Self::ElseCk { size, .. } => *size as i32,
+            Self::For { size, .. } | Self::While { size, .. } | Self::Do { size, .. } | Self::Switch { size, .. } | Self::Case { size, .. } => *size as i32,
+            Self::Goto { offset, .. } => *offset as i32,
+            _ => return None,
+        })
+    }
+
+    /// The name of the field holding this instruction's branch offset
+    pub const fn branch_field(&self) -> Option<&'static str> {
+        match self {
+            Self::Goto { .. } => Some("offset"),
+            _ if self.branch_offset().is_some() => Some("size"),
+            _ => None,
+        }
+    }
+
+    /// Given the offset of this instruction in its function, get the offset it branches to
+    pub const fn branch_target(&self, offset: usize) -> Option<isize> {
+        match self.branch_offset() {
+            Some(branch) => Some(offset as isize + branch as isize),
+            None => None,
+        }
+    }
+
     pub const fn is_check(&self) -> bool {
         matches!(self,
-            Self::Ck { .. } | Self::Cmp { .. } | Self::DirCk { .. } | Self::SceKeyCk { .. } | Self::SceTrgCk { .. }
+            Self::Ck { .. } | Self::Cmp { .. } | Self::MemberCmp { .. } | Self::DirCk { .. } | Self::SceKeyCk { .. } | Self::SceTrgCk { .. }
             | Self::KeepItemCk(_) | Self::KeepItemCk2 { .. } | Self::SceEmPosCk { .. } | Self::PoisonCk
         )
     }
 
+    /// Whether this instruction modifies the room's lights
+    pub const fn is_light(&self) -> bool {
+        matches!(self,
+            Self::LightPosSet { .. } | Self::LightPosSet2 { .. } | Self::LightKidoSet { .. } | Self::LightKidoSet2 { .. }
+            | Self::LightColorSet { .. } | Self::LightColorSet2 { .. }
+        )
+    }
+
+    /// The camera modified by a lighting instruction that targets a specific camera
+    pub const fn light_cut(&self) -> Option<u8> {
+        match self {
+            Self::LightPosSet2 { n_cut, .. } | Self::LightKidoSet2 { n_cut, .. } | Self::LightColorSet2 { n_cut, .. } => Some(*n_cut),
+            _ => None,
+        }
+    }
+
+    pub const fn info(&self) -> &'static InstructionInfo {
+        match self {
+            Self::Unknown { .. } => &UNKNOWN_INFO,
+            _ => &INSTRUCTION_INFO[self.opcode() as usize],
+        }
+    }
+
+    /// The encoded size of the instruction in bytes
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::Unknown { bytes, .. } => 1 + bytes.len(),
+            _ => self.info().size(),
+        }
+    }
+
+    /// Encode the instruction into bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::with_capacity(self.size()));
+        // writing to a Vec can't fail
+        writer.write_le(self).unwrap();
+        writer.into_inner()
+    }
+
+    /// The values of the instruction's fields in the order listed by its [`InstructionInfo`]
+    pub fn field_values(&self) -> Vec<i32> {
+        let bytes = self.to_bytes();
+        let mut offset = 1;
+        let mut values = Vec::with_capacity(self.info().fields.len());
+        for field in self.info().fields {
+            values.push(field.type_.read(&bytes[offset..]));
+            offset += field.type_.size();
+        }
+        values
+    }
+
+    pub fn field_value(&self, name: &str) -> Option<i32> {
+        let (index, _) = self.info().field(name)?;
+        self.field_values().get(index).copied()
+    }
+
+    /// Build an instruction from its opcode and field values
+    ///
+    /// There must be one value per field listed in the instruction's [`InstructionInfo`].
+    pub fn from_fields(opcode: u8, values: &[i32]) -> Result<Self> {
+        let Some(info) = InstructionInfo::for_opcode(opcode) else {
+            bail!("Invalid opcode {:#04x}", opcode);
+        };
+
+        if values.len() != info.fields.len() {
+            bail!("{} takes {} fields but {} were given", info.mnemonic, info.fields.len(), values.len());
+        }
+
+        let mut bytes = Vec::with_capacity(info.size());
+        bytes.push(opcode);
+        for (field, &value) in info.fields.iter().zip(values) {
+            let (min, max) = field.type_.range();
+            if value < min || value > max {
+                bail!("Value {} is out of range for field {} of {}", value, field.name, info.mnemonic);
+            }
+
+            match field.type_.size() {
+                1 => bytes.push(value as u8),
+                _ => bytes.extend_from_slice(&(value as u16).to_le_bytes()),
+            }
+        }
+
+        Ok(Cursor::new(bytes).read_le()?)
+    }
+
     pub const fn opcode(&self) -> u8 {
         match self {
             Self::Nop => 0x00,
@@ -747,6 +1051,33 @@ impl Instruction {
             Self::VibFadeSet { .. } => 0x8C,
             Self::ItemAotSet2 { .. } => 0x8D,
             Self::SceEmSet2 { .. } => 0x8E,
+            Self::Unknown { opcode, .. } => *opcode,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info() {
+        for opcode in 0..=MAX_OPCODE {
+            let info = InstructionInfo::for_opcode(opcode).unwrap();
+            let values = vec![0; info.fields.len()];
+            let inst = Instruction::from_fields(opcode, &values).unwrap();
+            assert_eq!(inst.opcode(), opcode);
+            assert_eq!(inst.to_bytes().len(), info.size(), "{}", info.name);
+            assert_eq!(InstructionInfo::for_mnemonic(info.mnemonic).unwrap().0, opcode);
+        }
+    }
+
+    #[test]
+    fn test_fields() {
+        let inst = Instruction::Cmp { align: 0, member: 3, operator: 2, value: -5 };
+        assert_eq!(inst.field_values(), vec![0, 3, 2, -5]);
+        assert_eq!(inst.field_value("value"), Some(-5));
+        assert_eq!(Instruction::from_fields(0x23, &[0, 3, 2, -5]).unwrap(), inst);
+        assert!(Instruction::from_fields(0x23, &[0, 3, 2, 70000]).is_err());
+    }
+}
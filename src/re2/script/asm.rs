@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::{CalcOp, CompareOp, FlagBank, Instruction, InstructionInfo, UNKNOWN_INFO};
+use crate::re2::{Item, SceType};
+
+const COMMENT_CHAR: char = ';';
+const LABEL_CHAR: char = '@';
+
+/// An instruction argument before labels have been resolved
+#[derive(Debug)]
+enum Arg<'a> {
+    Positional(&'a str),
+    Named(&'a str, &'a str),
+}
+
+#[derive(Debug)]
+struct ParsedLine<'a> {
+    line_number: usize,
+    offset: usize,
+    opcode: u8,
+    info: &'static InstructionInfo,
+    args: Vec<Arg<'a>>,
+}
+
+/// The kinds of symbolic names a field's values can have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolKind {
+    Sce,
+    Item,
+    Compare,
+    Calc,
+    FlagBank,
+}
+
+impl SymbolKind {
+    fn for_field(info: &InstructionInfo, field: &str) -> Option<Self> {
+        match (info.name, field) {
+            (_, "sce") => Some(Self::Sce),
+            (_, "i_item" | "item" | "item_id") => Some(Self::Item),
+            ("Cmp" | "MemberCmp", "operator") => Some(Self::Compare),
+            ("Calc" | "Calc2" | "MemberCalc" | "MemberCalc2", "operator") => Some(Self::Calc),
+            ("Ck" | "Set", "flag") => Some(Self::FlagBank),
+            _ => None,
+        }
+    }
+
+    fn name(&self, value: i32) -> Option<String> {
+        match self {
+            Self::Sce => u8::try_from(value).ok()
+                .map(SceType::from)
+                .filter(|sce| *sce != SceType::Unknown)
+                .map(|sce| format!("{:?}", sce)),
+            Self::Item => u16::try_from(value).ok()
+                .and_then(|id| Item::try_from(id).ok())
+                .map(|item| format!("{:?}", item)),
+            Self::Compare => u8::try_from(value).ok()
+                .and_then(|op| CompareOp::try_from(op).ok())
+                .map(|op| format!("{:?}", op)),
+            Self::Calc => u8::try_from(value).ok()
+                .and_then(|op| CalcOp::try_from(op).ok())
+                .map(|op| format!("{:?}", op)),
+            Self::FlagBank => u8::try_from(value).ok()
+                .and_then(|bank| FlagBank::try_from(bank).ok())
+                .map(|bank| format!("{:?}", bank)),
+        }
+    }
+
+    fn value(&self, symbol: &str) -> Option<i32> {
+        (0..=u8::MAX as i32).find(|&value| self.name(value).as_deref() == Some(symbol))
+    }
+}
+
+/// Look up the value of a symbolic name for a field
+///
+/// `sce` fields accept [`SceType`] variant names, item fields accept [`Item`] variant names, and
+/// the `operator` fields of comparisons and calculations accept [`CompareOp`] and [`CalcOp`]
+/// variant names, and the `flag` fields of `Ck` and `Set` accept [`FlagBank`] variant names.
+pub(super) fn symbol_value(info: &InstructionInfo, field: &str, symbol: &str) -> Option<i32> {
+    SymbolKind::for_field(info, field)?.value(symbol)
+}
+
+/// Get the symbolic name of a field's value, if it has one
+///
+/// This is the inverse of [`symbol_value`].
+pub(super) fn symbol_name(info: &InstructionInfo, field: &str, value: i32) -> Option<String> {
+    SymbolKind::for_field(info, field)?.name(value)
+}
+
+fn parse_int(s: &str) -> Option<i32> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => i32::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+
+    Some(if negative { -value } else { value })
+}
+
+impl Instruction {
+    /// Assemble a script function from its text representation
+    ///
+    /// Each line holds one instruction: its mnemonic from [`InstructionInfo`] followed by its field
+    /// values, either in order or as `name=value` pairs. Named fields that are omitted default to
+    /// 0. Values may be decimal or `0x`-prefixed hexadecimal integers, [`SceType`], [`Item`],
+    /// [`CompareOp`], [`CalcOp`], or [`FlagBank`] variant names for the fields that hold them, or
+    /// `@label` to use the distance in bytes from the start of the instruction to the label. A label
+    /// is defined by `label:` at the start of a line, and `;` starts a comment.
+    ///
+    /// ```text
+    /// if_el_ck size=@else
+    ///     ck flag=Scenario id=0x2a on_off=1
+    ///     aot_set aot=3 sce=Door sat=0x01 x=-1000 z=2000 w=1500 h=1500
+    /// else: end_if
+    /// evt_end
+    /// ```
+    pub fn assemble(source: &str) -> Result<Vec<Self>> {
+        let mut labels = HashMap::new();
+        let mut lines = Vec::new();
+        let mut offset = 0usize;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let mut line = line.split(COMMENT_CHAR).next().unwrap_or_default().trim();
+
+            if let Some((label, rest)) = line.split_once(':') {
+                let label = label.trim();
+                if label.is_empty() || label.contains(char::is_whitespace) || label.contains('=') {
+                    bail!("Line {}: invalid label {:?}", line_number, label);
+                }
+
+                if labels.insert(label, offset).is_some() {
+                    bail!("Line {}: duplicate label {:?}", line_number, label);
+                }
+                line = rest.trim();
+            }
+
+            let mut tokens = line.split_whitespace();
+            let Some(mnemonic) = tokens.next() else {
+                continue;
+            };
+
+            let (opcode, info) = if mnemonic == UNKNOWN_INFO.mnemonic {
+                (0, &UNKNOWN_INFO)
+            } else {
+                InstructionInfo::for_mnemonic(mnemonic)
+                    .ok_or_else(|| anyhow!("Line {}: unknown instruction {:?}", line_number, mnemonic))?
+            };
+
+            let args: Vec<_> = tokens.map(|token| match token.split_once('=') {
+                Some((name, value)) => Arg::Named(name, value),
+                None => Arg::Positional(token),
+            }).collect();
+
+            // the raw bytes of an unknown instruction, opcode included, are its arguments
+            let size = if info == &UNKNOWN_INFO { args.len() } else { info.size() };
+            lines.push(ParsedLine { line_number, offset, opcode, info, args });
+            offset += size;
+        }
+
+        lines.into_iter()
+            .map(|line| {
+                let line_number = line.line_number;
+                Self::assemble_line(line, &labels).with_context(|| format!("Line {}", line_number))
+            })
+            .collect()
+    }
+
+    fn assemble_line(line: ParsedLine, labels: &HashMap<&str, usize>) -> Result<Self> {
+        let info = line.info;
+        if info == &UNKNOWN_INFO {
+            return Self::assemble_unknown(&line.args);
+        }
+
+        let mut values = vec![None; info.fields.len()];
+        let mut next_positional = 0;
+
+        for arg in &line.args {
+            let (index, value) = match *arg {
+                Arg::Positional(value) => {
+                    if next_positional >= info.fields.len() {
+                        bail!("{} takes at most {} fields", info.mnemonic, info.fields.len());
+                    }
+                    next_positional += 1;
+                    (next_positional - 1, value)
+                }
+                Arg::Named(name, value) => {
+                    let Some((index, _)) = info.field(name) else {
+                        bail!("{} has no field {:?}", info.mnemonic, name);
+                    };
+                    (index, value)
+                }
+            };
+
+            if values[index].is_some() {
+                bail!("Field {} was given more than once", info.fields[index].name);
+            }
+
+            let field = &info.fields[index];
+            let value = if let Some(label) = value.strip_prefix(LABEL_CHAR) {
+                let target = *labels.get(label).ok_or_else(|| anyhow!("Undefined label {:?}", label))?;
+                target as i32 - line.offset as i32
+            } else {
+                parse_int(value)
+                    .or_else(|| symbol_value(info, field.name, value))
+                    .ok_or_else(|| anyhow!("Invalid value {:?} for field {}", value, field.name))?
+            };
+
+            values[index] = Some(value);
+        }
+
+        let values: Vec<_> = values.into_iter().map(Option::unwrap_or_default).collect();
+        Self::from_fields(line.opcode, &values)
+    }
+
+    fn assemble_unknown(args: &[Arg]) -> Result<Self> {
+        let mut bytes = Vec::with_capacity(args.len());
+        for arg in args {
+            let Arg::Positional(value) = *arg else {
+                bail!("{} only takes byte values", UNKNOWN_INFO.mnemonic);
+            };
+            let byte = parse_int(value)
+                .and_then(|value| u8::try_from(value).ok())
+                .ok_or_else(|| anyhow!("Invalid byte {:?}", value))?;
+            bytes.push(byte);
+        }
+
+        if bytes.is_empty() {
+            bail!("{} needs at least an opcode", UNKNOWN_INFO.mnemonic);
+        }
+        let opcode = bytes.remove(0);
+        Ok(Self::Unknown { opcode, bytes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::*;
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            ; open the door once the flag is set
+            if_el_ck size=@else
+                ck flag=1 id=0x2a on_off=1
+                aot_set aot=3 sce=Door sat=0x01 x=-1000 z=2000 w=1500 h=1500
+            else: end_if 0
+            item_aot_set aot=4 sce=Item i_item=HandgunAmmo n_item=15
+            evt_end
+        ";
+
+        let script = Instruction::assemble(source).unwrap();
+        assert_eq!(script.len(), 6);
+        assert_eq!(script[0], Instruction::IfElCk { align: 0, size: 4 + 4 + 20 });
+        assert_eq!(script[2], Instruction::AotSet {
+            aot: 3,
+            sce: 1,
+            sat: 1,
+            n_floor: 0,
+            super_: 0,
+            x: Fixed16(-1000),
+            z: Fixed16(2000),
+            w: UFixed16(1500),
+            h: UFixed16(1500),
+            data0: 0,
+            data1: 0,
+            data2: 0,
+        });
+        assert!(matches!(script[4], Instruction::ItemAotSet { i_item: 20, n_item: 15, .. }));
+        assert_eq!(script[5], Instruction::EvtEnd(0));
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert!(Instruction::assemble("bogus 1").is_err());
+        assert!(Instruction::assemble("ck flag=1 nope=2").is_err());
+        assert!(Instruction::assemble("goto offset=@missing").is_err());
+        assert!(Instruction::assemble("evt_end 1 2").is_err());
+        assert!(Instruction::assemble("sleeping 70000").is_err());
+        assert!(Instruction::assemble("unknown").is_err());
+        assert!(Instruction::assemble("unknown 0x100").is_err());
+    }
+
+    #[test]
+    fn test_assemble_unknown() {
+        let script = Instruction::assemble("goto offset=@end\nunknown 0xa0 1 0x02\nend: evt_end").unwrap();
+        assert_eq!(script[0].branch_offset(), Some(6 + 3));
+        assert_eq!(script[1], Instruction::Unknown { opcode: 0xA0, bytes: vec![1, 2] });
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Write};
+use std::ops::Range;
+
+use super::{Disassembly, Instruction};
+use crate::re2::export::dot_escape;
+
+/// How control passes along an edge of a [`ControlFlowGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Execution continues with the next instruction
+    Next,
+    /// A check in a condition failed
+    ConditionFalse,
+    /// An unconditional jump from `ElseCk`, `Goto`, or `Break`
+    Jump,
+    /// The end of a loop body jumping back to the start of the loop
+    LoopBack,
+    /// A loop that runs no further iterations
+    LoopExit,
+    /// A switch dispatching to one of its cases, or past the end if no case matches
+    Case,
+}
+
+impl EdgeKind {
+    const fn label(&self) -> Option<&'static str> {
+        match self {
+            Self::Next => None,
+            Self::ConditionFalse => Some("false"),
+            Self::Jump => Some("jump"),
+            Self::LoopBack => Some("loop"),
+            Self::LoopExit => Some("exit"),
+            Self::Case => Some("case"),
+        }
+    }
+}
+
+/// A run of instructions that always execute in sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Byte offset of the first instruction in the function
+    pub start: usize,
+    /// Byte offset just past the last instruction
+    pub end: usize,
+    /// Indexes of the block's instructions in the function
+    pub instructions: Range<usize>,
+}
+
+/// An edge between two basic blocks, identified by their indexes in [`ControlFlowGraph::blocks`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// A branch whose target is not the start of an instruction in the function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidBranch {
+    /// Byte offset of the branching instruction
+    pub offset: usize,
+    pub target: isize,
+    pub kind: EdgeKind,
+}
+
+/// The basic blocks of a script function and the control flow between them
+///
+/// Branches to the end of the function and instructions that end the function (`EvtEnd` and
+/// `Return`) leave the graph without an edge. Calls to other functions with `GoSub` are treated
+/// as ordinary instructions.
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph<'a> {
+    function: &'a [Instruction],
+    offsets: Vec<usize>,
+    blocks: Vec<BasicBlock>,
+    edges: Vec<Edge>,
+    invalid_branches: Vec<InvalidBranch>,
+}
+
+impl<'a> ControlFlowGraph<'a> {
+    pub fn new(function: &'a [Instruction]) -> Self {
+        let mut offsets = Vec::with_capacity(function.len() + 1);
+        let mut offset = 0usize;
+        offsets.push(offset);
+        for instruction in function {
+            offset += instruction.size();
+            offsets.push(offset);
+        }
+        let end = offset;
+
+        let successors = Self::successors(function, &offsets);
+
+        let index_at = |target: isize| -> Option<usize> {
+            usize::try_from(target).ok().and_then(|target| offsets[..function.len()].binary_search(&target).ok())
+        };
+
+        // a block starts at the beginning of the function, at each branch target, and after each
+        // instruction that does anything other than continue to the next instruction
+        let mut leaders = HashSet::new();
+        if !function.is_empty() {
+            leaders.insert(0);
+        }
+        for (i, instruction_successors) in successors.iter().enumerate() {
+            let just_next = matches!(instruction_successors[..], [(_, EdgeKind::Next)]);
+            if !just_next && i + 1 < function.len() {
+                leaders.insert(i + 1);
+            }
+            for &(target, kind) in instruction_successors {
+                if kind == EdgeKind::Next {
+                    continue;
+                }
+                if let Some(index) = index_at(target) {
+                    leaders.insert(index);
+                }
+            }
+        }
+
+        let mut leaders: Vec<_> = leaders.into_iter().collect();
+        leaders.sort_unstable();
+        let blocks: Vec<_> = leaders.iter().enumerate()
+            .map(|(i, &first)| {
+                let last = leaders.get(i + 1).copied().unwrap_or(function.len());
+                BasicBlock { start: offsets[first], end: offsets[last], instructions: first..last }
+            })
+            .collect();
+
+        let mut edges = Vec::new();
+        let mut invalid_branches = Vec::new();
+        for (from, block) in blocks.iter().enumerate() {
+            let last = block.instructions.end - 1;
+            for &(target, kind) in &successors[last] {
+                if target == end as isize {
+                    continue;
+                }
+
+                match index_at(target).and_then(|index| leaders.binary_search(&index).ok()) {
+                    Some(to) => edges.push(Edge { from, to, kind }),
+                    None => invalid_branches.push(InvalidBranch { offset: offsets[last], target, kind }),
+                }
+            }
+        }
+
+        Self { function, offsets, blocks, edges, invalid_branches }
+    }
+
+    /// Find the offsets each instruction can pass control to
+    fn successors(function: &[Instruction], offsets: &[usize]) -> Vec<Vec<(isize, EdgeKind)>> {
+        let len = function.len();
+        // match block openers with their closers, cases with their switches, and breaks with the
+        // loop or switch they leave
+        let mut closers = vec![None; len];
+        let mut cases = vec![Vec::new(); len];
+        let mut break_owners = vec![None; len];
+        let mut stack: Vec<usize> = Vec::new();
+        for (i, instruction) in function.iter().enumerate() {
+            match instruction {
+                Instruction::Case { .. } | Instruction::Default(_) => {
+                    if let Some(&owner) = stack.last()
+                        && matches!(function[owner], Instruction::Switch { .. }) {
+                        cases[owner].push(i);
+                    }
+                }
+                Instruction::Break(_) => {
+                    break_owners[i] = stack.iter().rev().copied().find(|&owner| !matches!(function[owner], Instruction::IfElCk { .. }));
+                }
+                _ if instruction.decreases_nesting() => {
+                    if let Some(owner) = stack.pop() {
+                        closers[owner] = Some(i);
+                    }
+                }
+                _ => (),
+            }
+
+            if instruction.increases_nesting() {
+                stack.push(i);
+            }
+        }
+
+        let mut openers = vec![None; len];
+        for (owner, closer) in closers.iter().enumerate() {
+            if let Some(closer) = *closer {
+                openers[closer] = Some(owner);
+            }
+        }
+
+        let at = |index: usize| offsets[index] as isize;
+        let mut successors = vec![Vec::new(); len];
+        let mut condition_target = None;
+        for (i, instruction) in function.iter().enumerate() {
+            let offset = offsets[i];
+            let next = at(i + 1);
+            let branch = instruction.branch_target(offset);
+            let edges = &mut successors[i];
+
+            if instruction.is_check() {
+                if let Some(target) = condition_target {
+                    edges.push((target, EdgeKind::ConditionFalse));
+                }
+            } else {
+                condition_target = None;
+            }
+
+            match instruction {
+                Instruction::EvtEnd(_) | Instruction::Return(_) => (),
+                Instruction::IfElCk { .. } | Instruction::While { .. } => {
+                    edges.push((next, EdgeKind::Next));
+                    let kind = if matches!(instruction, Instruction::While { .. }) { EdgeKind::LoopExit } else { EdgeKind::ConditionFalse };
+                    edges.extend(branch.map(|target| (target, kind)));
+                    condition_target = branch;
+                }
+                Instruction::ElseCk { .. } | Instruction::Goto { .. } => {
+                    edges.extend(branch.map(|target| (target, EdgeKind::Jump)));
+                }
+                Instruction::For { .. } => {
+                    edges.push((next, EdgeKind::Next));
+                    edges.extend(branch.map(|target| (target, EdgeKind::LoopExit)));
+                }
+                Instruction::For2 { .. } => {
+                    edges.push((next, EdgeKind::Next));
+                    if let Some(closer) = closers[i] {
+                        edges.push((at(closer + 1), EdgeKind::LoopExit));
+                    }
+                }
+                Instruction::Next(_) | Instruction::EWhile(_) | Instruction::EdWhile(_) => {
+                    if let Some(owner) = openers[i] {
+                        // a while loop re-checks its condition while the others resume the body
+                        let start = if matches!(instruction, Instruction::EWhile(_)) { at(owner) } else { at(owner + 1) };
+                        edges.push((start, EdgeKind::LoopBack));
+                    }
+                    edges.push((next, EdgeKind::Next));
+                }
+                Instruction::Switch { .. } => {
+                    edges.extend(cases[i].iter().map(|&case| (at(case), EdgeKind::Case)));
+                    if !cases[i].iter().any(|&case| matches!(function[case], Instruction::Default(_))) {
+                        edges.extend(branch.map(|target| (target, EdgeKind::Case)));
+                    }
+                }
+                Instruction::Break(_) => match break_owners[i].and_then(|owner| closers[owner]) {
+                    Some(closer) => edges.push((at(closer + 1), EdgeKind::Jump)),
+                    None => edges.push((next, EdgeKind::Next)),
+                },
+                _ => edges.push((next, EdgeKind::Next)),
+            }
+        }
+
+        // the checks at the end of a do-while body leave the loop when they fail
+        for (i, instruction) in function.iter().enumerate() {
+            if !matches!(instruction, Instruction::EdWhile(_)) {
+                continue;
+            }
+            let Some(owner) = openers[i] else {
+                continue;
+            };
+            let Some(exit) = function[owner].branch_target(offsets[owner]) else {
+                continue;
+            };
+
+            for check in (0..i).rev().take_while(|&j| j > owner && function[j].is_check()) {
+                successors[check].push((exit, EdgeKind::ConditionFalse));
+            }
+        }
+
+        successors
+    }
+
+    pub fn function(&self) -> &'a [Instruction] {
+        self.function
+    }
+
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Branches whose targets don't land on an instruction boundary in the function
+    pub fn invalid_branches(&self) -> &[InvalidBranch] {
+        &self.invalid_branches
+    }
+
+    /// The byte offset of each instruction in the function
+    pub fn offset(&self, index: usize) -> usize {
+        self.offsets[index]
+    }
+
+    /// Get the index of the block containing the given byte offset
+    pub fn block_at(&self, offset: usize) -> Option<usize> {
+        match self.blocks.binary_search_by_key(&offset, |block| block.start) {
+            Ok(index) => Some(index),
+            Err(0) => None,
+            Err(index) => (offset < self.blocks[index - 1].end).then_some(index - 1),
+        }
+    }
+
+    pub fn successors_of(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == block)
+    }
+
+    pub fn predecessors_of(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.to == block)
+    }
+
+    /// Determine which blocks can be reached from the start of the function
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut pending = Vec::new();
+        if !self.blocks.is_empty() {
+            pending.push(0);
+        }
+
+        while let Some(block) = pending.pop() {
+            if reachable[block] {
+                continue;
+            }
+            reachable[block] = true;
+            pending.extend(self.successors_of(block).map(|edge| edge.to));
+        }
+
+        reachable
+    }
+
+    /// The indexes of blocks that can never execute
+    pub fn unreachable_blocks(&self) -> Vec<usize> {
+        self.reachable().into_iter()
+            .enumerate()
+            .filter_map(|(i, reachable)| (!reachable).then_some(i))
+            .collect()
+    }
+
+    /// Render the graph in Graphviz DOT format
+    pub fn to_dot(&self, name: &str) -> String {
+        let boundaries: HashSet<_> = self.offsets.iter().copied().collect();
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", dot_escape(name)).unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for index in block.instructions.clone() {
+                let line = InstructionLine {
+                    instruction: &self.function[index],
+                    offset: self.offsets[index],
+                    boundaries: &boundaries,
+                };
+                write!(label, "{}\\l", dot_escape(&line.to_string())).unwrap();
+            }
+            writeln!(dot, "    b{} [label=\"{}\"];", i, label).unwrap();
+        }
+
+        for edge in &self.edges {
+            match edge.kind.label() {
+                Some(label) => writeln!(dot, "    b{} -> b{} [label=\"{}\"];", edge.from, edge.to, label).unwrap(),
+                None => writeln!(dot, "    b{} -> b{};", edge.from, edge.to).unwrap(),
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+struct InstructionLine<'a> {
+    instruction: &'a Instruction,
+    offset: usize,
+    boundaries: &'a HashSet<usize>,
+}
+
+impl Display for InstructionLine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04x}: ", self.offset)?;
+        Disassembly::format_instruction(f, self.instruction, self.offset, self.boundaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_else() {
+        let source = "
+            if_el_ck size=@else
+                ck flag=1 id=42 on_off=1
+                cut_chg 2
+            else_ck size=@end
+            else: cut_chg 3
+            end: end_if
+            evt_end
+        ";
+        let function = Instruction::assemble(source).unwrap();
+        let cfg = ControlFlowGraph::new(&function);
+
+        // [if_el_ck] [ck] [cut_chg 2, else_ck] [cut_chg 3] [end_if, evt_end]
+        assert_eq!(cfg.blocks().len(), 5);
+        assert_eq!(cfg.blocks()[2].instructions, 2..4);
+        assert!(cfg.edges().contains(&Edge { from: 0, to: 3, kind: EdgeKind::ConditionFalse }));
+        assert!(cfg.edges().contains(&Edge { from: 1, to: 3, kind: EdgeKind::ConditionFalse }));
+        assert!(cfg.edges().contains(&Edge { from: 2, to: 4, kind: EdgeKind::Jump }));
+        assert!(cfg.edges().contains(&Edge { from: 3, to: 4, kind: EdgeKind::Next }));
+        assert_eq!(cfg.successors_of(4).count(), 0);
+        assert!(cfg.unreachable_blocks().is_empty());
+        assert!(cfg.invalid_branches().is_empty());
+        assert_eq!(cfg.block_at(cfg.offset(3)), Some(2));
+
+        let dot = cfg.to_dot("test");
+        assert!(dot.starts_with("digraph \"test\" {"));
+        assert!(dot.contains("b0 -> b3 [label=\"false\"];"));
+    }
+
+    #[test]
+    fn test_loops_and_dead_code() {
+        let source = "
+            for size=@after count=3
+                sleeping 10
+                break
+                cut_chg 1
+            next
+            after: goto offset=@end
+            cut_chg 2
+            end: evt_end
+        ";
+        let function = Instruction::assemble(source).unwrap();
+        let cfg = ControlFlowGraph::new(&function);
+
+        let after = cfg.block_at(cfg.offset(5)).unwrap();
+        let body = cfg.block_at(cfg.offset(1)).unwrap();
+        let next = cfg.block_at(cfg.offset(4)).unwrap();
+        assert!(cfg.edges().contains(&Edge { from: 0, to: after, kind: EdgeKind::LoopExit }));
+        assert!(cfg.edges().contains(&Edge { from: body, to: after, kind: EdgeKind::Jump }));
+        assert!(cfg.edges().contains(&Edge { from: next, to: body, kind: EdgeKind::LoopBack }));
+
+        // the code after the break and after the goto never runs
+        let dead: Vec<_> = cfg.unreachable_blocks().into_iter().map(|block| cfg.blocks()[block].instructions.clone()).collect();
+        assert_eq!(dead, vec![3..5, 6..7]);
+    }
+
+    #[test]
+    fn test_invalid_branch() {
+        let function = vec![Instruction::Goto { ifel_ctr: 0, loop_ctr: 0, align: 0, offset: 3 }, Instruction::EvtEnd(0)];
+        let cfg = ControlFlowGraph::new(&function);
+        assert_eq!(cfg.invalid_branches(), &[InvalidBranch { offset: 0, target: 3, kind: EdgeKind::Jump }]);
+    }
+}
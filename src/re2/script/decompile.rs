@@ -0,0 +1,464 @@
+use std::fmt::{Display, Formatter, Result};
+
+use super::{CalcOp, CompareOp, Instruction, SymbolTable};
+use super::asm::symbol_name;
+
+const INDENT: &str = "    ";
+
+/// The label of a case in a switch statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaseLabel {
+    Value(u16),
+    Default,
+    /// Instructions between the `Switch` and its first case, which the game never executes
+    Unlabeled,
+}
+
+/// A case in a switch statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub label: CaseLabel,
+    pub body: Vec<Statement>,
+}
+
+/// A statement in a decompiled script function
+///
+/// Conditions are lists of check instructions (see [`Instruction::is_check`]) which must all pass.
+/// Instructions that only delimit blocks are absorbed into the block statements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Instruction(Instruction),
+    If {
+        conditions: Vec<Instruction>,
+        then_body: Vec<Statement>,
+        else_body: Option<Vec<Statement>>,
+    },
+    For {
+        count: u16,
+        body: Vec<Statement>,
+    },
+    ForRange {
+        start: i16,
+        end: i16,
+        body: Vec<Statement>,
+    },
+    While {
+        conditions: Vec<Instruction>,
+        body: Vec<Statement>,
+    },
+    DoWhile {
+        body: Vec<Statement>,
+        conditions: Vec<Instruction>,
+    },
+    Switch {
+        id: u8,
+        cases: Vec<Case>,
+    },
+}
+
+struct Parser<'a> {
+    instructions: &'a [Instruction],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Instruction> {
+        self.instructions.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Instruction> {
+        let instruction = self.peek()?;
+        self.pos += 1;
+        Some(instruction)
+    }
+
+    /// Consume the next instruction if it matches
+    fn accept(&mut self, matches: fn(&Instruction) -> bool) -> Option<&'a Instruction> {
+        self.peek().filter(|i| matches(i)).and_then(|_| self.next())
+    }
+
+    fn take_conditions(&mut self) -> Vec<Instruction> {
+        let mut conditions = Vec::new();
+        while let Some(check) = self.accept(Instruction::is_check) {
+            conditions.push(check.clone());
+        }
+        conditions
+    }
+
+    /// Parse statements until reaching an instruction accepted by `is_end`, which is not consumed
+    fn parse_block(&mut self, is_end: fn(&Instruction) -> bool) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        while let Some(instruction) = self.peek() {
+            if is_end(instruction) {
+                break;
+            }
+
+            self.pos += 1;
+            statements.push(self.parse_statement(instruction));
+        }
+        statements
+    }
+
+    fn parse_statement(&mut self, instruction: &'a Instruction) -> Statement {
+        match *instruction {
+            Instruction::IfElCk { .. } => {
+                let conditions = self.take_conditions();
+                let then_body = self.parse_block(|i| matches!(i, Instruction::ElseCk { .. } | Instruction::EndIf(_)));
+                let else_body = self.accept(|i| matches!(i, Instruction::ElseCk { .. }))
+                    .map(|_| self.parse_block(|i| matches!(i, Instruction::EndIf(_))));
+                self.accept(|i| matches!(i, Instruction::EndIf(_)));
+                Statement::If { conditions, then_body, else_body }
+            }
+            Instruction::For { count, .. } => {
+                let body = self.parse_block(|i| matches!(i, Instruction::Next(_)));
+                self.accept(|i| matches!(i, Instruction::Next(_)));
+                Statement::For { count, body }
+            }
+            Instruction::For2 { start_value, end_value, .. } => {
+                let body = self.parse_block(|i| matches!(i, Instruction::Next(_)));
+                self.accept(|i| matches!(i, Instruction::Next(_)));
+                Statement::ForRange { start: start_value, end: end_value, body }
+            }
+            Instruction::While { .. } => {
+                let conditions = self.take_conditions();
+                let body = self.parse_block(|i| matches!(i, Instruction::EWhile(_)));
+                self.accept(|i| matches!(i, Instruction::EWhile(_)));
+                Statement::While { conditions, body }
+            }
+            Instruction::Do { .. } => {
+                let mut body = self.parse_block(|i| matches!(i, Instruction::EdWhile(_)));
+                self.accept(|i| matches!(i, Instruction::EdWhile(_)));
+                // the loop condition is the run of checks at the end of the body
+                let num_conditions = body.iter().rev()
+                    .take_while(|s| matches!(s, Statement::Instruction(i) if i.is_check()))
+                    .count();
+                let conditions = body.split_off(body.len() - num_conditions).into_iter()
+                    .filter_map(|s| match s {
+                        Statement::Instruction(i) => Some(i),
+                        _ => None,
+                    })
+                    .collect();
+                Statement::DoWhile { body, conditions }
+            }
+            Instruction::Switch { id, .. } => {
+                let is_case_end = |i: &Instruction| matches!(i, Instruction::Case { .. } | Instruction::Default(_) | Instruction::ESwitch(_));
+                let mut cases = Vec::new();
+                let preamble = self.parse_block(is_case_end);
+                if !preamble.is_empty() {
+                    cases.push(Case { label: CaseLabel::Unlabeled, body: preamble });
+                }
+
+                while let Some(case) = self.accept(|i| matches!(i, Instruction::Case { .. } | Instruction::Default(_))) {
+                    let label = match *case {
+                        Instruction::Case { value, .. } => CaseLabel::Value(value),
+                        _ => CaseLabel::Default,
+                    };
+                    cases.push(Case { label, body: self.parse_block(is_case_end) });
+                }
+
+                self.accept(|i| matches!(i, Instruction::ESwitch(_)));
+                Statement::Switch { id, cases }
+            }
+            _ => Statement::Instruction(instruction.clone()),
+        }
+    }
+}
+
+/// A script function decompiled into structured statements
+///
+/// The `Display` implementation prints the function as C-like pseudocode. Use
+/// [`Decompilation::with_symbols`] to print flags and work variables by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decompilation {
+    pub statements: Vec<Statement>,
+}
+
+impl Decompilation {
+    pub fn new(function: &[Instruction]) -> Self {
+        let mut parser = Parser { instructions: function, pos: 0 };
+        let mut statements = Vec::new();
+        while parser.pos < function.len() {
+            // anything the top level doesn't expect, like a stray EndIf, is kept as an instruction
+            statements.extend(parser.parse_block(|_| false));
+        }
+
+        Self { statements }
+    }
+
+    /// Print the function with flags shown as `bank[id]` and flags and work variables named from
+    /// a symbol table
+    pub fn with_symbols<'a>(&'a self, symbols: &'a SymbolTable) -> impl Display + 'a {
+        WithSymbols { decompilation: self, symbols }
+    }
+
+    /// Write an instruction as a function call
+    pub fn write_call(f: &mut Formatter<'_>, instruction: &Instruction) -> Result {
+        let info = instruction.info();
+        write!(f, "{}(", info.mnemonic)?;
+        if let Instruction::Unknown { opcode, bytes } = instruction {
+            write!(f, "{:#04x}", opcode)?;
+            for byte in bytes {
+                write!(f, ", {:#04x}", byte)?;
+            }
+            return f.write_str(")");
+        }
+
+        let mut first = true;
+        for (field, value) in info.fields.iter().zip(instruction.field_values()) {
+            if field.is_align() {
+                continue;
+            }
+
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+
+            match symbol_name(info, field.name, value) {
+                Some(name) => write!(f, "{}={}", field.name, name)?,
+                None => write!(f, "{}={}", field.name, value)?,
+            }
+        }
+        f.write_str(")")
+    }
+
+    fn work(index: u8, symbols: Option<&SymbolTable>) -> String {
+        match symbols {
+            Some(symbols) => symbols.format_work(index),
+            None => format!("work[{}]", index),
+        }
+    }
+
+    fn write_conditions(f: &mut Formatter<'_>, conditions: &[Instruction], symbols: Option<&SymbolTable>) -> Result {
+        if conditions.is_empty() {
+            return f.write_str("true");
+        }
+
+        for (i, condition) in conditions.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" && ")?;
+            }
+
+            match *condition {
+                Instruction::Cmp { member, operator, value, .. } if let Ok(op) = CompareOp::try_from(operator) => {
+                    write!(f, "{} {} {}", Self::work(member, symbols), op.symbol(), value)?;
+                }
+                Instruction::Ck { flag, id, on_off } if let Some(symbols) = symbols => {
+                    let negation = if on_off == 0 { "!" } else { "" };
+                    write!(f, "{}{}", negation, symbols.format_flag(flag, id))?;
+                }
+                _ => Self::write_call(f, condition)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write an arithmetic instruction as an assignment, if it is one
+    ///
+    /// With a symbol table, `Set` is also written as an assignment to its flag.
+    fn write_assignment(f: &mut Formatter<'_>, instruction: &Instruction, symbols: Option<&SymbolTable>) -> Option<Result> {
+        let (op, flag, operand) = match *instruction {
+            Instruction::Calc { operator, flag, value, .. } => (operator, flag, value.to_string()),
+            Instruction::Calc2 { operator, flag, value } => (operator, flag, Self::work(value, symbols)),
+            Instruction::Set { flag, id, on_off } => {
+                return symbols.map(|symbols| write!(f, "{} = {}", symbols.format_flag(flag, id), on_off));
+            }
+            _ => return None,
+        };
+
+        let op = CalcOp::try_from(op).ok()?;
+        let target = Self::work(flag, symbols);
+        Some(match op.assign_symbol() {
+            Some(symbol) => write!(f, "{} {} {}", target, symbol, operand),
+            // work variables are signed, so a logical shift needs a cast
+            None if op == CalcOp::Shr => write!(f, "{} = (u16){} >> {}", target, target, operand),
+            None => write!(f, "{} = ~{}", target, target),
+        })
+    }
+
+    fn write_block(f: &mut Formatter<'_>, statements: &[Statement], depth: usize, symbols: Option<&SymbolTable>) -> Result {
+        for statement in statements {
+            Self::write_statement(f, statement, depth, symbols)?;
+        }
+        Ok(())
+    }
+
+    fn write_statement(f: &mut Formatter<'_>, statement: &Statement, depth: usize, symbols: Option<&SymbolTable>) -> Result {
+        let indent = INDENT.repeat(depth);
+        match statement {
+            Statement::Instruction(Instruction::Break(_)) => writeln!(f, "{}break;", indent),
+            Statement::Instruction(instruction) => {
+                f.write_str(&indent)?;
+                match Self::write_assignment(f, instruction, symbols) {
+                    Some(result) => result?,
+                    None => Self::write_call(f, instruction)?,
+                }
+                writeln!(f, ";")
+            }
+            Statement::If { conditions, then_body, else_body } => {
+                write!(f, "{}if (", indent)?;
+                Self::write_conditions(f, conditions, symbols)?;
+                writeln!(f, ") {{")?;
+                Self::write_block(f, then_body, depth + 1, symbols)?;
+                if let Some(else_body) = else_body {
+                    writeln!(f, "{}}} else {{", indent)?;
+                    Self::write_block(f, else_body, depth + 1, symbols)?;
+                }
+                writeln!(f, "{}}}", indent)
+            }
+            Statement::For { count, body } => {
+                writeln!(f, "{}for ({} times) {{", indent, count)?;
+                Self::write_block(f, body, depth + 1, symbols)?;
+                writeln!(f, "{}}}", indent)
+            }
+            Statement::ForRange { start, end, body } => {
+                writeln!(f, "{}for ({}..{}) {{", indent, start, end)?;
+                Self::write_block(f, body, depth + 1, symbols)?;
+                writeln!(f, "{}}}", indent)
+            }
+            Statement::While { conditions, body } => {
+                write!(f, "{}while (", indent)?;
+                Self::write_conditions(f, conditions, symbols)?;
+                writeln!(f, ") {{")?;
+                Self::write_block(f, body, depth + 1, symbols)?;
+                writeln!(f, "{}}}", indent)
+            }
+            Statement::DoWhile { body, conditions } => {
+                writeln!(f, "{}do {{", indent)?;
+                Self::write_block(f, body, depth + 1, symbols)?;
+                write!(f, "{}}} while (", indent)?;
+                Self::write_conditions(f, conditions, symbols)?;
+                writeln!(f, ");")
+            }
+            Statement::Switch { id, cases } => {
+                writeln!(f, "{}switch (work[{}]) {{", indent, id)?;
+                for case in cases {
+                    match case.label {
+                        CaseLabel::Value(value) => writeln!(f, "{}case {}:", indent, value)?,
+                        CaseLabel::Default => writeln!(f, "{}default:", indent)?,
+                        CaseLabel::Unlabeled => (),
+                    }
+                    Self::write_block(f, &case.body, depth + 1, symbols)?;
+                }
+                writeln!(f, "{}}}", indent)
+            }
+        }
+    }
+}
+
+impl Display for Decompilation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        Self::write_block(f, &self.statements, 0, None)
+    }
+}
+
+struct WithSymbols<'a> {
+    decompilation: &'a Decompilation,
+    symbols: &'a SymbolTable,
+}
+
+impl Display for WithSymbols<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        Decompilation::write_block(f, &self.decompilation.statements, 0, Some(self.symbols))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompile() {
+        let source = "
+            if_el_ck size=@else
+                ck flag=1 id=42 on_off=1
+                cut_chg 2
+            else_ck size=@end
+            else: for size=@next count=3
+                    sleeping 10
+                next
+            next: switch id=4 size=@end_switch
+                case size=@default value=1
+                    break
+                default: default
+                    set flag=1 id=42 on_off=0
+                eswitch
+            end_switch: end_if
+            end: evt_end
+        ";
+        let function = Instruction::assemble(source).unwrap();
+        let decompiled = Decompilation::new(&function);
+
+        assert_eq!(decompiled.statements.len(), 2);
+        let Statement::If { conditions, then_body, else_body: Some(else_body) } = &decompiled.statements[0] else {
+            panic!("expected if/else");
+        };
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(then_body, &[Statement::Instruction(Instruction::CutChg(2))]);
+        assert_eq!(else_body.len(), 2);
+
+        assert_eq!(decompiled.to_string(), "\
+if (ck(flag=Status, id=42, on_off=1)) {
+    cut_chg(cut=2);
+} else {
+    for (3 times) {
+        sleeping(count=10);
+    }
+    switch (work[4]) {
+    case 1:
+        break;
+    default:
+        set(flag=Status, id=42, on_off=0);
+    }
+}
+evt_end(value=0);
+");
+    }
+
+    #[test]
+    fn test_do_while() {
+        let source = "
+            do size=@end
+                sleep
+                calc operator=Shr flag=2 value=1
+                cmp member=1 operator=Ge value=5
+            ed_while
+            end: evt_end
+        ";
+        let function = Instruction::assemble(source).unwrap();
+        let decompiled = Decompilation::new(&function);
+        assert!(matches!(&decompiled.statements[0], Statement::DoWhile { body, conditions } if body.len() == 2 && conditions.len() == 1));
+        assert_eq!(decompiled.to_string(), "\
+do {
+    sleep();
+    work[2] = (u16)work[2] >> 1;
+} while (work[1] >= 5);
+evt_end(value=0);
+");
+    }
+
+    #[test]
+    fn test_with_symbols() {
+        let source = "
+            if_el_ck size=@end
+                ck flag=Scenario id=0x2a on_off=0
+                cmp member=1 operator=Eq value=0
+                set flag=Scenario id=0x2a on_off=1
+                calc2 operator=Add flag=1 value=2
+            end: end_if
+            evt_end
+        ";
+        let function = Instruction::assemble(source).unwrap();
+        let symbols = SymbolTable::parse("
+            scenario 0x2a met Ada
+            work 1 visits
+        ").unwrap();
+        assert_eq!(Decompilation::new(&function).with_symbols(&symbols).to_string(), "\
+if (!scenario[0x2A] \"met Ada\" && work[1] \"visits\" == 0) {
+    scenario[0x2A] \"met Ada\" = 1;
+    work[1] \"visits\" += work[2];
+}
+evt_end(value=0);
+");
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Result};
+
+use super::{Instruction, SymbolTable};
+use super::asm::symbol_name;
+
+const INDENT: &str = "    ";
+
+/// A disassembly listing of a script function
+///
+/// Each line starts with the instruction's byte offset in the function, which doubles as a label,
+/// so the listing can be fed back into [`Instruction::assemble`]. Blocks are indented by nesting
+/// depth, branch sizes and offsets are shown as the labels they target, and `sce` and item fields
+/// are shown by name where possible. With a [`SymbolTable`], the flags and work variables
+/// instructions refer to are named in a trailing comment.
+#[derive(Debug, Clone, Copy)]
+pub struct Disassembly<'a> {
+    function: &'a [Instruction],
+    symbols: Option<&'a SymbolTable>,
+}
+
+impl<'a> Disassembly<'a> {
+    pub const fn new(function: &'a [Instruction]) -> Self {
+        Self { function, symbols: None }
+    }
+
+    pub const fn with_symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Format a single instruction without its offset
+    pub fn format_instruction(f: &mut Formatter<'_>, instruction: &Instruction, offset: usize, boundaries: &HashSet<usize>) -> Result {
+        let info = instruction.info();
+        f.write_str(info.mnemonic)?;
+
+        if let Instruction::Unknown { opcode, bytes } = instruction {
+            write!(f, " {:#04x}", opcode)?;
+            for byte in bytes {
+                write!(f, " {:#04x}", byte)?;
+            }
+            return Ok(());
+        }
+
+        let branch_field = instruction.branch_field();
+        let branch_target = instruction.branch_target(offset);
+        for (field, value) in info.fields.iter().zip(instruction.field_values()) {
+            if field.is_align() && value == 0 {
+                continue;
+            }
+
+            write!(f, " {}=", field.name)?;
+            if branch_field == Some(field.name) {
+                match branch_target {
+                    Some(target) if target >= 0 && boundaries.contains(&(target as usize)) => write!(f, "@{:04x}", target)?,
+                    _ => write!(f, "{}", value)?,
+                }
+            } else if let Some(name) = symbol_name(info, field.name, value) {
+                f.write_str(&name)?;
+            } else {
+                write!(f, "{}", value)?;
+            }
+        }
+
+        if let Some(target) = branch_target
+            && (target < 0 || !boundaries.contains(&(target as usize))) {
+            write!(f, " ; target {:#x} is not an instruction boundary", target)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let mut boundaries = HashSet::with_capacity(self.function.len() + 1);
+        let mut offset = 0usize;
+        for instruction in self.function {
+            boundaries.insert(offset);
+            offset += instruction.size();
+        }
+        let end = offset;
+        boundaries.insert(end);
+
+        let mut depth = 0usize;
+        let mut offset = 0usize;
+        for instruction in self.function {
+            if instruction.decreases_nesting() {
+                depth = depth.saturating_sub(1);
+            }
+
+            let indent = if matches!(instruction, Instruction::ElseCk { .. } | Instruction::Case { .. } | Instruction::Default(_)) {
+                depth.saturating_sub(1)
+            } else {
+                depth
+            };
+
+            write!(f, "{:04x}: {}", offset, INDENT.repeat(indent))?;
+            Self::format_instruction(f, instruction, offset, &boundaries)?;
+            if let Some(operand) = self.symbols.and_then(|symbols| symbols.format_operand(instruction)) {
+                write!(f, " ; {}", operand)?;
+            }
+            writeln!(f)?;
+
+            if instruction.increases_nesting() {
+                depth += 1;
+            }
+            offset += instruction.size();
+        }
+
+        // emit a label for the end of the function if anything branches there
+        let branches_to_end = self.function.iter().scan(0usize, |offset, instruction| {
+            let target = instruction.branch_target(*offset);
+            *offset += instruction.size();
+            Some(target)
+        }).any(|target| target == Some(end as isize));
+        if branches_to_end {
+            writeln!(f, "{:04x}:", end)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A disassembly listing of a room's init and exec scripts
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptListing<'a> {
+    init_script: &'a [Vec<Instruction>],
+    exec_script: &'a [Vec<Instruction>],
+    symbols: Option<&'a SymbolTable>,
+}
+
+impl<'a> ScriptListing<'a> {
+    pub const fn new(init_script: &'a [Vec<Instruction>], exec_script: &'a [Vec<Instruction>]) -> Self {
+        Self { init_script, exec_script, symbols: None }
+    }
+
+    pub const fn with_symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+}
+
+impl Display for ScriptListing<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for (name, script) in [("Init", self.init_script), ("Exec", self.exec_script)] {
+            for (i, function) in script.iter().enumerate() {
+                writeln!(f, "; {} function {}", name, i)?;
+                let disassembly = Disassembly { function, symbols: self.symbols };
+                writeln!(f, "{}", disassembly)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassembly() {
+        let source = "
+            if_el_ck size=@else
+                ck flag=1 id=42 on_off=1
+                item_aot_set aot=4 sce=Item i_item=HandgunAmmo n_item=15
+            else_ck size=@end
+            else: set flag=1 id=42 on_off=0
+            end: end_if
+            evt_end
+        ";
+        let function = Instruction::assemble(source).unwrap();
+        let listing = Disassembly::new(&function).to_string();
+        assert_eq!(listing, "\
+0000: if_el_ck size=@0022
+0004:     ck flag=Status id=42 on_off=1
+0008:     item_aot_set aot=4 sce=Item sat=0 n_floor=0 super=0 x=0 z=0 w=0 h=0 i_item=HandgunAmmo n_item=15 flag=0 md1=0 action=0
+001e: else_ck size=@0026
+0022:     set flag=Status id=42 on_off=0
+0026: end_if
+0028: evt_end value=0
+");
+        assert_eq!(Instruction::assemble(&listing).unwrap(), function);
+    }
+
+    #[test]
+    fn test_symbols() {
+        let function = Instruction::assemble("
+            ck flag=Scenario id=0x2a on_off=1
+            calc operator=Add flag=3 value=1
+            evt_end
+        ").unwrap();
+        let symbols = SymbolTable::parse("scenario 0x2a met Ada").unwrap();
+        let listing = Disassembly::new(&function).with_symbols(&symbols).to_string();
+        assert_eq!(listing, "\
+0000: ck flag=Scenario id=42 on_off=1 ; scenario[0x2A] \"met Ada\"
+0004: calc operator=Add flag=3 value=1 ; work[3]
+000a: evt_end value=0
+");
+        assert_eq!(Instruction::assemble(&listing).unwrap(), function);
+    }
+
+    #[test]
+    fn test_unknown_round_trip() {
+        let function = vec![Instruction::Unknown { opcode: 0xA0, bytes: vec![1, 0xff] }, Instruction::EvtEnd(0)];
+        let listing = Disassembly::new(&function).to_string();
+        assert_eq!(listing, "0000: unknown 0xa0 0x01 0xff\n0003: evt_end value=0\n");
+        assert_eq!(Instruction::assemble(&listing).unwrap(), function);
+    }
+}
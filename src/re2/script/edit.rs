@@ -0,0 +1,323 @@
+use anyhow::{anyhow, bail, Result};
+
+use super::Instruction;
+
+/// An editable script function that keeps block sizes and branch offsets correct
+///
+/// Branches are tracked by the instruction they target rather than by byte offset. After every
+/// edit, the sizes of `IfElCk`, `ElseCk`, `For`, `While`, `Do`, `Switch`, and `Case` are
+/// recomputed from the block structure, following the convention described on
+/// [`Instruction::branch_offset`], and each `Goto` offset is updated to keep pointing at the same
+/// instruction. If the instruction a `Goto` targets is removed, the `Goto` targets the instruction
+/// that took its place. Blocks that aren't properly closed keep pointing at the same instruction
+/// like a `Goto`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionEditor {
+    instructions: Vec<Instruction>,
+    /// The index of the instruction each branching instruction targets, where the function length
+    /// means the end of the function
+    targets: Vec<Option<usize>>,
+}
+
+impl FunctionEditor {
+    /// Start editing a function
+    ///
+    /// Fails if a `Goto` doesn't target the start of an instruction in the function. Block sizes
+    /// that don't match the function's structure are corrected.
+    pub fn new(function: Vec<Instruction>) -> Result<Self> {
+        let offsets = Self::offsets(&function);
+        let mut targets = Vec::with_capacity(function.len());
+        for (i, instruction) in function.iter().enumerate() {
+            let target = instruction.branch_target(offsets[i]).and_then(|target| Self::index_at(&offsets, target));
+            if target.is_none() && matches!(instruction, Instruction::Goto { .. }) {
+                bail!("Goto at offset {:#x} does not target an instruction in the function", offsets[i]);
+            }
+            targets.push(target);
+        }
+
+        let mut editor = Self { instructions: function, targets };
+        editor.fix_branches()?;
+        Ok(editor)
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn into_instructions(self) -> Vec<Instruction> {
+        self.instructions
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// The byte offset of each instruction, followed by the size of the function
+    fn offsets(function: &[Instruction]) -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(function.len() + 1);
+        let mut offset = 0usize;
+        offsets.push(offset);
+        for instruction in function {
+            offset += instruction.size();
+            offsets.push(offset);
+        }
+        offsets
+    }
+
+    fn index_at(offsets: &[usize], target: isize) -> Option<usize> {
+        usize::try_from(target).ok().and_then(|target| offsets.binary_search(&target).ok())
+    }
+
+    /// Work out the targets of block instructions from the nesting of the function
+    fn structural_targets(function: &[Instruction]) -> Vec<Option<usize>> {
+        let mut targets = vec![None; function.len()];
+        // each open block, with its ElseCk or the last Case seen in it
+        let mut open_blocks: Vec<(usize, Option<usize>)> = Vec::new();
+        for (i, instruction) in function.iter().enumerate() {
+            match instruction {
+                Instruction::ElseCk { .. } => {
+                    if let Some((opener, inner)) = open_blocks.last_mut()
+                        && matches!(function[*opener], Instruction::IfElCk { .. }) {
+                        *inner = Some(i);
+                    }
+                }
+                Instruction::Case { .. } | Instruction::Default(_) => {
+                    if let Some((opener, inner)) = open_blocks.last_mut()
+                        && matches!(function[*opener], Instruction::Switch { .. })
+                        && let Some(case) = inner.replace(i) {
+                        targets[case] = Some(i);
+                    }
+                }
+                _ if instruction.decreases_nesting() => {
+                    if let Some(&(opener, inner)) = open_blocks.last()
+                        && instruction.closes(&function[opener]) {
+                        open_blocks.pop();
+                        match function[opener] {
+                            Instruction::IfElCk { .. } => {
+                                targets[opener] = Some(inner.map_or(i, |else_ck| else_ck + 1));
+                                if let Some(else_ck) = inner {
+                                    targets[else_ck] = Some(i);
+                                }
+                            }
+                            Instruction::Switch { .. } => {
+                                targets[opener] = Some(i + 1);
+                                if let Some(case) = inner.filter(|&case| matches!(function[case], Instruction::Case { .. })) {
+                                    targets[case] = Some(i);
+                                }
+                            }
+                            Instruction::For2 { .. } => (),
+                            _ => targets[opener] = Some(i + 1),
+                        }
+                    }
+                }
+                _ => (),
+            }
+
+            if instruction.increases_nesting() {
+                open_blocks.push((i, None));
+            }
+        }
+
+        targets
+    }
+
+    /// Rewrite every branch field to match the tracked targets
+    fn fix_branches(&mut self) -> Result<()> {
+        let structural = Self::structural_targets(&self.instructions);
+        let offsets = Self::offsets(&self.instructions);
+        for (i, instruction) in self.instructions.iter_mut().enumerate() {
+            let Some(field_name) = instruction.branch_field() else {
+                self.targets[i] = None;
+                continue;
+            };
+
+            let target = if matches!(instruction, Instruction::Goto { .. }) { self.targets[i] } else { structural[i].or(self.targets[i]) };
+            self.targets[i] = target;
+            let Some(target) = target else {
+                continue;
+            };
+
+            let offset = offsets[target] as i32 - offsets[i] as i32;
+            let info = instruction.info();
+            let (index, _) = info.field(field_name).ok_or_else(|| anyhow!("{} has no field {}", info.mnemonic, field_name))?;
+            let mut values = instruction.field_values();
+            if values[index] != offset {
+                values[index] = offset;
+                *instruction = Instruction::from_fields(instruction.opcode(), &values)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_index(&self, index: usize, allow_end: bool) -> Result<()> {
+        let len = self.instructions.len();
+        if index > len || (index == len && !allow_end) {
+            bail!("Index {} is out of range for a function with {} instructions", index, len);
+        }
+        Ok(())
+    }
+
+    /// Resolve the offset of a newly placed `Goto` against the current layout
+    fn resolve_goto(&mut self, index: usize) -> Result<()> {
+        let offsets = Self::offsets(&self.instructions);
+        let instruction = &self.instructions[index];
+        if let Instruction::Goto { .. } = instruction {
+            let target = instruction.branch_target(offsets[index]).and_then(|target| Self::index_at(&offsets, target));
+            if target.is_none() {
+                bail!("Goto at offset {:#x} does not target an instruction in the function", offsets[index]);
+            }
+            self.targets[index] = target;
+        } else if let Some(target) = instruction.branch_target(offsets[index]) {
+            self.targets[index] = Self::index_at(&offsets, target);
+        }
+        Ok(())
+    }
+
+    /// Insert an instruction before the instruction at the given index
+    ///
+    /// Branches to the instruction previously at `index` still target it. The offset of an
+    /// inserted `Goto` is relative to its position after it's been inserted.
+    pub fn insert(&mut self, index: usize, instruction: Instruction) -> Result<()> {
+        self.check_index(index, true)?;
+        let original = self.clone();
+
+        for target in self.targets.iter_mut().flatten() {
+            if *target >= index {
+                *target += 1;
+            }
+        }
+        self.instructions.insert(index, instruction);
+        self.targets.insert(index, None);
+
+        let result = self.resolve_goto(index).and_then(|_| self.fix_branches());
+        if result.is_err() {
+            *self = original;
+        }
+        result
+    }
+
+    /// Remove and return the instruction at the given index
+    pub fn remove(&mut self, index: usize) -> Result<Instruction> {
+        self.check_index(index, false)?;
+        let original = self.clone();
+
+        let instruction = self.instructions.remove(index);
+        self.targets.remove(index);
+        for target in self.targets.iter_mut().flatten() {
+            if *target > index {
+                *target -= 1;
+            }
+        }
+
+        match self.fix_branches() {
+            Ok(()) => Ok(instruction),
+            Err(e) => {
+                *self = original;
+                Err(e)
+            }
+        }
+    }
+
+    /// Replace the instruction at the given index, returning the old instruction
+    ///
+    /// The offset of a replacement `Goto` is relative to its position in the function.
+    pub fn replace(&mut self, index: usize, instruction: Instruction) -> Result<Instruction> {
+        self.check_index(index, false)?;
+        let original = self.clone();
+
+        let old = std::mem::replace(&mut self.instructions[index], instruction);
+        self.targets[index] = None;
+
+        match self.resolve_goto(index).and_then(|_| self.fix_branches()) {
+            Ok(()) => Ok(old),
+            Err(e) => {
+                *self = original;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assemble(source: &str) -> Vec<Instruction> {
+        Instruction::assemble(source).unwrap()
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut editor = FunctionEditor::new(assemble("
+            goto offset=@end
+            if_el_ck size=@else
+                ck flag=1 id=2 on_off=1
+            else_ck size=@end_if
+            else: switch id=0 size=@end_if
+                case size=@default value=1
+                default: default
+                eswitch
+            end_if: end_if
+            end: evt_end
+        ")).unwrap();
+
+        // grow the then branch, the else branch, and the first case
+        editor.insert(3, Instruction::CutChg(1)).unwrap();
+        editor.insert(5, Instruction::Sleeping(5)).unwrap();
+        editor.insert(8, Instruction::Sleep).unwrap();
+        assert_eq!(editor.instructions(), assemble("
+            goto offset=@end
+            if_el_ck size=@else
+                ck flag=1 id=2 on_off=1
+                cut_chg 1
+            else_ck size=@end_if
+            else: sleeping 5
+            switch id=0 size=@end_if
+                case size=@default value=1
+                    sleep
+                default: default
+                eswitch
+            end_if: end_if
+            end: evt_end
+        "));
+
+        assert_eq!(editor.remove(3).unwrap(), Instruction::CutChg(1));
+        assert_eq!(editor.remove(5).unwrap(), Instruction::Switch { id: 0, size: 15 });
+        assert_eq!(editor.instructions(), assemble("
+            goto offset=@end
+            if_el_ck size=@else
+                ck flag=1 id=2 on_off=1
+            else_ck size=@end_if
+            else: sleeping 5
+                case size=@default value=1
+                    sleep
+                default: default
+                eswitch
+            end_if: end_if
+            end: evt_end
+        "));
+    }
+
+    #[test]
+    fn test_goto_target_removed() {
+        let mut editor = FunctionEditor::new(assemble("
+            goto offset=@target
+            cut_chg 1
+            target: cut_chg 2
+            evt_end
+        ")).unwrap();
+        editor.remove(2).unwrap();
+        assert_eq!(editor.instructions()[0].branch_offset(), Some(6 + 2));
+
+        editor.replace(1, Instruction::Goto { ifel_ctr: 0, loop_ctr: 0, align: 0, offset: -6 }).unwrap();
+        editor.insert(1, Instruction::Sleep).unwrap();
+        assert_eq!(editor.instructions()[2].branch_offset(), Some(-7));
+        assert!(editor.insert(0, Instruction::Goto { ifel_ctr: 0, loop_ctr: 0, align: 0, offset: 1 }).is_err());
+        assert_eq!(editor.len(), 4);
+    }
+}
@@ -0,0 +1,812 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, bail, Result};
+
+use super::{CalcOp, CompareOp, Instruction, ScriptKind};
+
+/// Maximum number of instructions a thread may execute in one frame before we assume it's stuck
+const MAX_STEPS_PER_FRAME: usize = 10000;
+/// Maximum number of frames the init script may take to finish when entering a room
+const MAX_INIT_FRAMES: u32 = 1000;
+
+/// Game state that scripts read and modify
+///
+/// Flags are addressed by bank and bit. Work variables are the scenario variables used by `Save`,
+/// `Copy`, `Calc`, `Calc2`, `Cmp`, and `Switch`. Member variables belong to the entity selected
+/// with `WorkSet`. Everything not set explicitly reads as zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptState {
+    flags: HashSet<(u8, u8)>,
+    work: HashMap<u8, i16>,
+    members: HashMap<(u8, u8, u16), i16>,
+    inventory: HashMap<u8, u32>,
+}
+
+impl ScriptState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn flag(&self, bank: u8, id: u8) -> bool {
+        self.flags.contains(&(bank, id))
+    }
+
+    pub fn set_flag(&mut self, bank: u8, id: u8, on: bool) {
+        if on {
+            self.flags.insert((bank, id));
+        } else {
+            self.flags.remove(&(bank, id));
+        }
+    }
+
+    pub fn work(&self, index: u8) -> i16 {
+        self.work.get(&index).copied().unwrap_or_default()
+    }
+
+    pub fn set_work(&mut self, index: u8, value: i16) {
+        self.work.insert(index, value);
+    }
+
+    /// Get a member variable of the entity with the given work type and ID
+    pub fn member(&self, work_type: u8, entity_id: u8, member: u16) -> i16 {
+        self.members.get(&(work_type, entity_id, member)).copied().unwrap_or_default()
+    }
+
+    pub fn set_member(&mut self, work_type: u8, entity_id: u8, member: u16, value: i16) {
+        self.members.insert((work_type, entity_id, member), value);
+    }
+
+    pub fn item_count(&self, item: u8) -> u32 {
+        self.inventory.get(&item).copied().unwrap_or_default()
+    }
+
+    pub fn add_item(&mut self, item: u8, quantity: u32) {
+        *self.inventory.entry(item).or_default() += quantity;
+    }
+
+    /// Remove up to `quantity` of an item from the inventory
+    pub fn remove_item(&mut self, item: u8, quantity: u32) {
+        let count = self.item_count(item).saturating_sub(quantity);
+        if count == 0 {
+            self.inventory.remove(&item);
+        } else {
+            self.inventory.insert(item, count);
+        }
+    }
+}
+
+/// A side effect of running a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptEvent {
+    ThreadStarted { script: ScriptKind, function: usize },
+    ThreadEnded,
+    ThreadKilled { thread: u8 },
+    /// An AOT was set with `AotSet`, `DoorAotSet`, `ItemAotSet`, or one of their variants
+    AotSet { aot: u8, instruction: Instruction },
+    AotReset { aot: u8 },
+    AotOn { aot: u8 },
+    EnemySpawned { id: u8, instruction: Instruction },
+    MessageShown { message: u8 },
+    CutChanged { cut: u8 },
+    ItemReceived { item: u8, quantity: u8 },
+    ItemLost { item: u8, quantity: u8 },
+}
+
+/// A [`ScriptEvent`] along with when and where it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRecord {
+    pub frame: u32,
+    pub thread: u8,
+    pub event: ScriptEvent,
+}
+
+/// An open block in a function, pointing at instruction indexes
+#[derive(Debug, Clone, Copy)]
+enum Block {
+    If { else_target: usize },
+    For { body: usize, exit: usize, remaining: u16 },
+    ForRange { body: usize, exit: usize, current: i16, end: i16 },
+    While { start: usize, exit: usize },
+    Do { body: usize, exit: usize },
+    Switch { exit: usize },
+}
+
+impl Block {
+    const fn is_if(&self) -> bool {
+        matches!(self, Self::If { .. })
+    }
+
+    const fn exit(&self) -> Option<usize> {
+        match *self {
+            Self::If { .. } => None,
+            Self::For { exit, .. } | Self::ForRange { exit, .. } | Self::While { exit, .. } | Self::Do { exit, .. } | Self::Switch { exit } => Some(exit),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    function: usize,
+    pc: usize,
+    blocks: Vec<Block>,
+}
+
+impl Frame {
+    const fn new(function: usize) -> Self {
+        Self { function, pc: 0, blocks: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Thread {
+    script: ScriptKind,
+    frames: Vec<Frame>,
+    sleep: u16,
+    work_type: u8,
+    entity_id: u8,
+    started: u32,
+}
+
+type WorldCheck<'a> = Box<dyn FnMut(&Instruction) -> bool + 'a>;
+
+enum Flow {
+    Continue,
+    Yield,
+    End,
+}
+
+/// An interpreter that runs a room's scripts outside the game
+///
+/// Scripts run in threads, one per slot, the way `EvtExec` starts them in the game. Each frame,
+/// every thread runs until it sleeps or ends. Instructions that affect the game world are recorded
+/// as [`ScriptEvent`]s rather than simulated; checks that depend on the game world (`DirCk`,
+/// `SceKeyCk`, `SceTrgCk`, `SceEmPosCk`, and `PoisonCk`) fail unless a handler is provided with
+/// [`Interpreter::set_world_check`].
+///
+/// Where the game's exact behavior is unknown, the interpreter makes these assumptions:
+/// - Work variables hold `i16` values, and `Cmp`'s `member` field is the work variable it compares.
+/// - `For2` counts from its start value up to, but not including, its end value.
+/// - `EvtExec`'s `data` field is the thread slot, which `EvtKill` also uses.
+/// - `EvtEnd` returns from a function called with `GoSub`, like `Return`.
+/// - `Goto` closes open blocks until no more than `ifel_ctr` ifs and `loop_ctr` loops remain.
+pub struct Interpreter<'a> {
+    init_script: &'a [Vec<Instruction>],
+    exec_script: &'a [Vec<Instruction>],
+    offsets: HashMap<(ScriptKind, usize), Vec<usize>>,
+    threads: Vec<Option<Thread>>,
+    current_replaced: bool,
+    world_check: Option<WorldCheck<'a>>,
+    frame: u32,
+    pub state: ScriptState,
+    events: Vec<EventRecord>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(init_script: &'a [Vec<Instruction>], exec_script: &'a [Vec<Instruction>]) -> Self {
+        Self::with_state(init_script, exec_script, ScriptState::new())
+    }
+
+    pub fn with_state(init_script: &'a [Vec<Instruction>], exec_script: &'a [Vec<Instruction>], state: ScriptState) -> Self {
+        let mut offsets = HashMap::new();
+        for (kind, script) in [(ScriptKind::Init, init_script), (ScriptKind::Exec, exec_script)] {
+            for (i, function) in script.iter().enumerate() {
+                let mut offset = 0usize;
+                let mut function_offsets = Vec::with_capacity(function.len() + 1);
+                function_offsets.push(offset);
+                for instruction in function {
+                    offset += instruction.size();
+                    function_offsets.push(offset);
+                }
+                offsets.insert((kind, i), function_offsets);
+            }
+        }
+
+        Self {
+            init_script,
+            exec_script,
+            offsets,
+            threads: Vec::new(),
+            current_replaced: false,
+            world_check: None,
+            frame: 0,
+            state,
+            events: Vec::new(),
+        }
+    }
+
+    /// Set the handler that decides the result of checks that depend on the game world
+    pub fn set_world_check(&mut self, check: impl FnMut(&Instruction) -> bool + 'a) {
+        self.world_check = Some(Box::new(check));
+    }
+
+    pub const fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn events(&self) -> &[EventRecord] {
+        &self.events
+    }
+
+    /// Remove and return the events recorded so far
+    pub fn take_events(&mut self) -> Vec<EventRecord> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn into_state(self) -> ScriptState {
+        self.state
+    }
+
+    /// The slots of threads that are still running
+    pub fn active_threads(&self) -> impl Iterator<Item = u8> + '_ {
+        self.threads.iter().enumerate().filter_map(|(i, thread)| thread.as_ref().map(|_| i as u8))
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.threads.iter().all(Option::is_none)
+    }
+
+    const fn script(&self, kind: ScriptKind) -> &'a [Vec<Instruction>] {
+        match kind {
+            ScriptKind::Init => self.init_script,
+            ScriptKind::Exec => self.exec_script,
+        }
+    }
+
+    fn record(&mut self, thread: u8, event: ScriptEvent) {
+        self.events.push(EventRecord { frame: self.frame, thread, event });
+    }
+
+    /// Start running a function in the given thread slot, replacing any thread already there
+    ///
+    /// The thread starts running on the next frame.
+    pub fn start_thread(&mut self, slot: u8, script: ScriptKind, function: usize) -> Result<()> {
+        if function >= self.script(script).len() {
+            bail!("{:?} script has no function {}", script, function);
+        }
+
+        let slot_index = slot as usize;
+        if self.threads.len() <= slot_index {
+            self.threads.resize(slot_index + 1, None);
+        }
+        self.threads[slot_index] = Some(Thread {
+            script,
+            frames: vec![Frame::new(function)],
+            sleep: 0,
+            work_type: 0,
+            entity_id: 0,
+            started: self.frame,
+        });
+        self.record(slot, ScriptEvent::ThreadStarted { script, function });
+        Ok(())
+    }
+
+    pub fn kill_thread(&mut self, slot: u8) {
+        if let Some(thread) = self.threads.get_mut(slot as usize) {
+            *thread = None;
+        }
+    }
+
+    /// Simulate entering the room
+    ///
+    /// The first init function runs until it finishes, then the first exec function is started in
+    /// thread 0.
+    pub fn enter_room(&mut self) -> Result<()> {
+        if !self.init_script.is_empty() {
+            self.start_thread(0, ScriptKind::Init, 0)?;
+            let mut frames = 0;
+            while !self.is_idle() {
+                if frames >= MAX_INIT_FRAMES {
+                    bail!("Init script did not finish within {} frames", MAX_INIT_FRAMES);
+                }
+                self.step_frame()?;
+                frames += 1;
+            }
+        }
+
+        if !self.exec_script.is_empty() {
+            self.start_thread(0, ScriptKind::Exec, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the given number of frames, stopping early if all threads end
+    pub fn run(&mut self, frames: u32) -> Result<()> {
+        for _ in 0..frames {
+            if self.is_idle() {
+                break;
+            }
+            self.step_frame()?;
+        }
+        Ok(())
+    }
+
+    /// Run every thread until it sleeps or ends
+    pub fn step_frame(&mut self) -> Result<()> {
+        self.frame += 1;
+        for slot in 0..self.threads.len() {
+            let Some(mut thread) = self.threads[slot].take_if(|thread| thread.started < self.frame) else {
+                continue;
+            };
+
+            self.current_replaced = false;
+            let alive = self.run_thread(&mut thread, slot as u8)
+                .map_err(|e| anyhow!("Thread {} ({:?} function {}): {}", slot, thread.script, thread.frames.last().map_or(0, |f| f.function), e))?;
+            if self.current_replaced {
+                continue;
+            }
+
+            if alive {
+                self.threads[slot] = Some(thread);
+            } else {
+                self.record(slot as u8, ScriptEvent::ThreadEnded);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_thread(&mut self, thread: &mut Thread, slot: u8) -> Result<bool> {
+        if thread.sleep > 0 {
+            thread.sleep -= 1;
+            return Ok(true);
+        }
+
+        for _ in 0..MAX_STEPS_PER_FRAME {
+            let Some(frame) = thread.frames.last_mut() else {
+                return Ok(false);
+            };
+
+            let function = &self.script(thread.script)[frame.function];
+            let Some(instruction) = function.get(frame.pc) else {
+                // falling off the end of a function returns from it
+                thread.frames.pop();
+                continue;
+            };
+            frame.pc += 1;
+
+            match self.execute(thread, slot, instruction)? {
+                Flow::Continue => (),
+                Flow::Yield => return Ok(true),
+                Flow::End => return Ok(false),
+            }
+
+            if self.current_replaced {
+                return Ok(false);
+            }
+        }
+
+        bail!("Exceeded {} instructions in one frame", MAX_STEPS_PER_FRAME)
+    }
+
+    /// Convert a byte offset in a thread's current function to an instruction index
+    fn index_at(&self, thread: &Thread, offset: isize) -> Result<usize> {
+        let frame = thread.frames.last().ok_or_else(|| anyhow!("Thread has no function"))?;
+        let offsets = &self.offsets[&(thread.script, frame.function)];
+        usize::try_from(offset).ok()
+            .and_then(|offset| offsets.binary_search(&offset).ok())
+            .ok_or_else(|| anyhow!("Branch target {:#x} is not an instruction boundary", offset))
+    }
+
+    /// The index of the instruction an instruction at the given index branches to
+    fn branch_index(&self, thread: &Thread, index: usize, instruction: &Instruction) -> Result<usize> {
+        let frame = thread.frames.last().ok_or_else(|| anyhow!("Thread has no function"))?;
+        let offset = self.offsets[&(thread.script, frame.function)][index];
+        let target = instruction.branch_target(offset).ok_or_else(|| anyhow!("{} does not branch", instruction.info().mnemonic))?;
+        self.index_at(thread, target)
+    }
+
+    /// Find the index of the instruction that closes the block opened at the given index
+    fn closer_index(function: &[Instruction], opener: usize) -> Result<usize> {
+        let mut depth = 0usize;
+        for (i, instruction) in function.iter().enumerate().skip(opener) {
+            if instruction.increases_nesting() {
+                depth += 1;
+            } else if instruction.decreases_nesting() {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+        }
+
+        bail!("Block at index {} is never closed", opener)
+    }
+
+    fn evaluate_check(&mut self, thread: &Thread, instruction: &Instruction) -> Result<bool> {
+        Ok(match *instruction {
+            Instruction::Ck { flag, id, on_off } => self.state.flag(flag, id) == (on_off != 0),
+            Instruction::Cmp { member, operator, value, .. } => compare(operator, self.state.work(member), value)?,
+            Instruction::MemberCmp { flag, operator, value, .. } => {
+                compare(operator, self.state.member(thread.work_type, thread.entity_id, flag as u16), value)?
+            }
+            Instruction::KeepItemCk(item) => self.state.item_count(item) > 0,
+            Instruction::KeepItemCk2 { item_id, quantity } => self.state.item_count(item_id) >= quantity as u32,
+            _ => match self.world_check.as_mut() {
+                Some(check) => check(instruction),
+                None => false,
+            },
+        })
+    }
+
+    /// Handle a failed check by leaving the condition it belongs to
+    fn fail_condition(frame: &mut Frame) {
+        match frame.blocks.last().copied() {
+            Some(Block::If { else_target }) => frame.pc = else_target,
+            Some(Block::While { exit, .. } | Block::Do { exit, .. }) => {
+                frame.blocks.pop();
+                frame.pc = exit;
+            }
+            // a check outside of a condition has nothing to skip
+            _ => (),
+        }
+    }
+
+    fn execute(&mut self, thread: &mut Thread, slot: u8, instruction: &Instruction) -> Result<Flow> {
+        if instruction.is_check() {
+            if !self.evaluate_check(thread, instruction)? {
+                Self::fail_condition(thread.frames.last_mut().unwrap());
+            }
+            return Ok(Flow::Continue);
+        }
+
+        let index = thread.frames.last().unwrap().pc - 1;
+        let function = &self.script(thread.script)[thread.frames.last().unwrap().function];
+        match *instruction {
+            Instruction::EvtEnd(_) | Instruction::Return(_) => {
+                thread.frames.pop();
+                if thread.frames.is_empty() {
+                    return Ok(Flow::End);
+                }
+            }
+            Instruction::EvtNext | Instruction::EvtNext2 | Instruction::Sleep | Instruction::WSleep | Instruction::WSleeping => return Ok(Flow::Yield),
+            Instruction::Sleeping(count) => {
+                thread.sleep = (count.max(1) - 1) as u16;
+                return Ok(Flow::Yield);
+            }
+            Instruction::EvtExec { data, scd_id, .. } => {
+                self.start_thread(data, thread.script, scd_id as usize)?;
+                self.current_replaced |= data == slot;
+            }
+            Instruction::EvtKill(target) => {
+                self.kill_thread(target);
+                self.record(slot, ScriptEvent::ThreadKilled { thread: target });
+                if target == slot {
+                    return Ok(Flow::End);
+                }
+            }
+            Instruction::GoSub(function) => {
+                if function as usize >= self.script(thread.script).len() {
+                    bail!("GoSub to missing function {}", function);
+                }
+                thread.frames.push(Frame::new(function as usize));
+            }
+            Instruction::IfElCk { .. } => {
+                let else_target = self.branch_index(thread, index, instruction)?;
+                thread.frames.last_mut().unwrap().blocks.push(Block::If { else_target });
+            }
+            Instruction::ElseCk { .. } => {
+                let target = self.branch_index(thread, index, instruction)?;
+                thread.frames.last_mut().unwrap().pc = target;
+            }
+            Instruction::EndIf(_) => {
+                let frame = thread.frames.last_mut().unwrap();
+                if frame.blocks.last().is_some_and(Block::is_if) {
+                    frame.blocks.pop();
+                }
+            }
+            Instruction::For { count, .. } => {
+                let exit = self.branch_index(thread, index, instruction)?;
+                let frame = thread.frames.last_mut().unwrap();
+                if count == 0 {
+                    frame.pc = exit;
+                } else {
+                    frame.blocks.push(Block::For { body: index + 1, exit, remaining: count });
+                }
+            }
+            Instruction::For2 { start_value, end_value, .. } => {
+                let exit = Self::closer_index(function, index)? + 1;
+                let frame = thread.frames.last_mut().unwrap();
+                if start_value >= end_value {
+                    frame.pc = exit;
+                } else {
+                    frame.blocks.push(Block::ForRange { body: index + 1, exit, current: start_value, end: end_value });
+                }
+            }
+            Instruction::Next(_) => {
+                let frame = thread.frames.last_mut().unwrap();
+                match frame.blocks.last_mut() {
+                    Some(Block::For { body, remaining, .. }) => {
+                        *remaining -= 1;
+                        if *remaining > 0 {
+                            frame.pc = *body;
+                        } else {
+                            frame.blocks.pop();
+                        }
+                    }
+                    Some(Block::ForRange { body, current, end, .. }) => {
+                        *current += 1;
+                        if *current < *end {
+                            frame.pc = *body;
+                        } else {
+                            frame.blocks.pop();
+                        }
+                    }
+                    _ => bail!("next without a matching for"),
+                }
+            }
+            Instruction::While { .. } => {
+                let exit = self.branch_index(thread, index, instruction)?;
+                thread.frames.last_mut().unwrap().blocks.push(Block::While { start: index, exit });
+            }
+            Instruction::EWhile(_) => {
+                let frame = thread.frames.last_mut().unwrap();
+                let Some(Block::While { start, .. }) = frame.blocks.pop() else {
+                    bail!("ewhile without a matching while");
+                };
+                frame.pc = start;
+                // yield so a loop waiting on the game world doesn't spin forever
+                return Ok(Flow::Yield);
+            }
+            Instruction::Do { .. } => {
+                let exit = self.branch_index(thread, index, instruction)?;
+                thread.frames.last_mut().unwrap().blocks.push(Block::Do { body: index + 1, exit });
+            }
+            Instruction::EdWhile(_) => {
+                let frame = thread.frames.last_mut().unwrap();
+                let Some(Block::Do { body, .. }) = frame.blocks.last() else {
+                    bail!("ed_while without a matching do");
+                };
+                frame.pc = *body;
+                return Ok(Flow::Yield);
+            }
+            Instruction::Switch { id, .. } => {
+                let exit = self.branch_index(thread, index, instruction)?;
+                let value = self.state.work(id);
+                let mut case_index = index + 1;
+                let target = loop {
+                    match function.get(case_index) {
+                        Some(case @ Instruction::Case { value: case_value, .. }) => {
+                            if *case_value as i16 == value {
+                                break Some(case_index + 1);
+                            }
+                            case_index = self.branch_index(thread, case_index, case)?;
+                        }
+                        Some(Instruction::Default(_)) => break Some(case_index + 1),
+                        Some(Instruction::ESwitch(_)) | None => break None,
+                        Some(_) => case_index += 1,
+                    }
+                };
+
+                let frame = thread.frames.last_mut().unwrap();
+                match target {
+                    Some(target) => {
+                        frame.blocks.push(Block::Switch { exit });
+                        frame.pc = target;
+                    }
+                    None => frame.pc = exit,
+                }
+            }
+            Instruction::ESwitch(_) => {
+                let frame = thread.frames.last_mut().unwrap();
+                if matches!(frame.blocks.last(), Some(Block::Switch { .. })) {
+                    frame.blocks.pop();
+                }
+            }
+            Instruction::Break(_) => {
+                let frame = thread.frames.last_mut().unwrap();
+                while let Some(block) = frame.blocks.pop() {
+                    if let Some(exit) = block.exit() {
+                        frame.pc = exit;
+                        break;
+                    }
+                }
+            }
+            Instruction::Goto { ifel_ctr, loop_ctr, .. } => {
+                let target = self.branch_index(thread, index, instruction)?;
+                let frame = thread.frames.last_mut().unwrap();
+                loop {
+                    let ifs = frame.blocks.iter().filter(|block| block.is_if()).count();
+                    let loops = frame.blocks.len() - ifs;
+                    if ifs <= ifel_ctr as usize && loops <= loop_ctr as usize {
+                        break;
+                    }
+                    frame.blocks.pop();
+                }
+                frame.pc = target;
+            }
+            Instruction::Set { flag, id, on_off } => self.state.set_flag(flag, id, on_off != 0),
+            Instruction::Save { destination, source } => self.state.set_work(destination, source),
+            Instruction::Copy { destination, source } => self.state.set_work(destination, self.state.work(source)),
+            Instruction::Calc { operator, flag, value, .. } => {
+                let result = calculate(operator, self.state.work(flag), value)?;
+                self.state.set_work(flag, result);
+            }
+            Instruction::Calc2 { operator, flag, value } => {
+                let result = calculate(operator, self.state.work(flag), self.state.work(value))?;
+                self.state.set_work(flag, result);
+            }
+            Instruction::WorkSet { type_, entity_id } => {
+                thread.work_type = type_;
+                thread.entity_id = entity_id;
+            }
+            Instruction::MemberSet { destination, source } => {
+                self.state.set_member(thread.work_type, thread.entity_id, destination as u16, source);
+            }
+            Instruction::MemberSet2 { destination, source } => {
+                self.state.set_member(thread.work_type, thread.entity_id, destination as u16, self.state.work(source));
+            }
+            Instruction::MemberCopy { destination, source } => {
+                let value = self.state.member(thread.work_type, thread.entity_id, source as u16);
+                self.state.set_work(destination, value);
+            }
+            Instruction::MemberCalc { operator, flag, value } => {
+                let current = self.state.member(thread.work_type, thread.entity_id, flag);
+                let result = calculate(operator, current, value)?;
+                self.state.set_member(thread.work_type, thread.entity_id, flag, result);
+            }
+            Instruction::MemberCalc2 { operator, flag, value } => {
+                let current = self.state.member(thread.work_type, thread.entity_id, flag as u16);
+                let result = calculate(operator, current, self.state.work(value))?;
+                self.state.set_member(thread.work_type, thread.entity_id, flag as u16, result);
+            }
+            Instruction::AotSet { .. } | Instruction::AotSet4p { .. } | Instruction::DoorAotSet { .. } | Instruction::DoorAotSet4p { .. }
+            | Instruction::ItemAotSet { .. } | Instruction::ItemAotSet4p { .. } | Instruction::ItemAotSet2 { .. } => {
+                let aot = instruction.field_value("aot").unwrap_or_default() as u8;
+                self.record(slot, ScriptEvent::AotSet { aot, instruction: instruction.clone() });
+            }
+            Instruction::AotReset { aot, .. } => self.record(slot, ScriptEvent::AotReset { aot: aot as u8 }),
+            Instruction::AotOn(aot) => self.record(slot, ScriptEvent::AotOn { aot: aot as u8 }),
+            Instruction::SceEmSet { id, .. } | Instruction::SceEmSet2 { aot: id, .. } => {
+                self.record(slot, ScriptEvent::EnemySpawned { id, instruction: instruction.clone() });
+            }
+            Instruction::MessageOn { message, .. } => self.record(slot, ScriptEvent::MessageShown { message }),
+            Instruction::CutChg(cut) => self.record(slot, ScriptEvent::CutChanged { cut }),
+            Instruction::SceItemGet { id, num } => {
+                self.state.add_item(id, num as u32);
+                self.record(slot, ScriptEvent::ItemReceived { item: id, quantity: num });
+            }
+            Instruction::SceItemLost(item) => {
+                let quantity = self.state.item_count(item);
+                self.state.remove_item(item, quantity);
+                self.record(slot, ScriptEvent::ItemLost { item, quantity: quantity.min(u8::MAX as u32) as u8 });
+            }
+            Instruction::SceItemLost2 { item_id, quantity } => {
+                self.state.remove_item(item_id, quantity as u32);
+                self.record(slot, ScriptEvent::ItemLost { item: item_id, quantity });
+            }
+            _ => (),
+        }
+
+        Ok(Flow::Continue)
+    }
+}
+
+/// Compare a value against an operand with one of the `Cmp` operators
+fn compare(operator: u8, value: i16, operand: i16) -> Result<bool> {
+    let op = CompareOp::try_from(operator).map_err(|_| anyhow!("Unknown comparison operator {}", operator))?;
+    Ok(op.evaluate(value, operand))
+}
+
+/// Apply one of the `Calc` operators
+fn calculate(operator: u8, value: i16, operand: i16) -> Result<i16> {
+    let op = CalcOp::try_from(operator).map_err(|_| anyhow!("Unknown calculation operator {}", operator))?;
+    op.evaluate(value, operand).ok_or_else(|| anyhow!("Division by zero"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assemble(functions: &[&str]) -> Vec<Vec<Instruction>> {
+        functions.iter().map(|source| Instruction::assemble(source).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_conditions_and_loops() {
+        let init = assemble(&["
+            set flag=1 id=3 on_off=1
+            if_el_ck size=@else
+                ck flag=1 id=3 on_off=1
+                save destination=0 source=2
+            else_ck size=@end
+            else: save destination=0 source=7
+            end: end_if
+            for size=@after count=3
+                calc operator=0 flag=0 value=10
+            next
+            after: item_aot_set aot=2 sce=Item i_item=HandgunAmmo n_item=15
+            evt_end
+        "]);
+        let mut interpreter = Interpreter::new(&init, &[]);
+        interpreter.enter_room().unwrap();
+
+        assert!(interpreter.is_idle());
+        assert_eq!(interpreter.state.work(0), 32);
+        assert!(interpreter.events().iter().any(|record| matches!(record.event, ScriptEvent::AotSet { aot: 2, .. })));
+    }
+
+    #[test]
+    fn test_member_cmp() {
+        let init = assemble(&["
+            work_set type=1 entity_id=2
+            member_set destination=4 source=7
+            if_el_ck size=@else
+                member_cmp align=0 flag=4 operator=2 value=5
+                save destination=0 source=1
+            else_ck size=@end
+            else: save destination=0 source=2
+            end: end_if
+            work_set type=1 entity_id=3
+            if_el_ck size=@else2
+                member_cmp align=0 flag=4 operator=2 value=5
+                save destination=1 source=1
+            else_ck size=@end2
+            else2: save destination=1 source=2
+            end2: end_if
+            evt_end
+        "]);
+        let mut interpreter = Interpreter::new(&init, &[]);
+        interpreter.enter_room().unwrap();
+
+        assert_eq!(interpreter.state.member(1, 2, 4), 7);
+        assert_eq!(interpreter.state.work(0), 1);
+        assert_eq!(interpreter.state.work(1), 2);
+    }
+
+    #[test]
+    fn test_threads() {
+        let exec = assemble(&[
+            "
+                evt_exec data=1 go_sub=0x18 scd_id=1
+                sleep
+                sleeping 3
+                message_on message=5
+                evt_end
+            ",
+            "
+                go_sub 2
+                cut_chg 4
+                evt_end
+            ",
+            "
+                switch id=0 size=@end
+                    case size=@next value=1
+                        set flag=2 id=0 on_off=1
+                        break
+                    next: default
+                        set flag=2 id=1 on_off=1
+                    eswitch
+                end: return
+            ",
+        ]);
+        let mut interpreter = Interpreter::new(&[], &exec);
+        interpreter.state.set_work(0, 1);
+        interpreter.enter_room().unwrap();
+        interpreter.run(10).unwrap();
+
+        assert!(interpreter.is_idle());
+        assert!(interpreter.state.flag(2, 0));
+        assert!(!interpreter.state.flag(2, 1));
+
+        let events: Vec<_> = interpreter.events().iter()
+            .filter(|record| matches!(record.event, ScriptEvent::CutChanged { .. } | ScriptEvent::MessageShown { .. }))
+            .map(|record| (record.frame, record.thread, record.event.clone()))
+            .collect();
+        assert_eq!(events, vec![
+            (2, 1, ScriptEvent::CutChanged { cut: 4 }),
+            (5, 0, ScriptEvent::MessageShown { message: 5 }),
+        ]);
+    }
+
+    #[test]
+    fn test_calculate() {
+        assert_eq!(calculate(1, 5, 7).unwrap(), -2);
+        assert_eq!(calculate(10, -1, 12).unwrap(), 0xf);
+        assert_eq!(calculate(11, -16, 2).unwrap(), -4);
+        assert!(calculate(3, 1, 0).is_err());
+        assert!(calculate(12, 1, 0).is_err());
+        assert!(compare(6, 0b1010, 0b0010).unwrap());
+        assert!(compare(7, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+/// A comparison operator used by `Cmp` and `MemberCmp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum CompareOp {
+    Eq = 0,
+    Gt = 1,
+    Ge = 2,
+    Lt = 3,
+    Le = 4,
+    Ne = 5,
+    /// Passes if the value and operand have any bits in common
+    And = 6,
+}
+
+impl CompareOp {
+    pub const fn evaluate(&self, value: i16, operand: i16) -> bool {
+        match self {
+            Self::Eq => value == operand,
+            Self::Gt => value > operand,
+            Self::Ge => value >= operand,
+            Self::Lt => value < operand,
+            Self::Le => value <= operand,
+            Self::Ne => value != operand,
+            Self::And => value & operand != 0,
+        }
+    }
+
+    /// The C operator for this comparison
+    pub const fn symbol(&self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Ne => "!=",
+            Self::And => "&",
+        }
+    }
+}
+
+/// An arithmetic operator used by `Calc`, `Calc2`, `MemberCalc`, and `MemberCalc2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum CalcOp {
+    Add = 0,
+    Sub = 1,
+    Mul = 2,
+    Div = 3,
+    Mod = 4,
+    Or = 5,
+    And = 6,
+    Xor = 7,
+    /// Bitwise not of the value, ignoring the operand
+    Not = 8,
+    Shl = 9,
+    /// Logical shift right
+    Shr = 10,
+    /// Arithmetic shift right
+    Sar = 11,
+}
+
+impl CalcOp {
+    /// Apply the operator, wrapping on overflow
+    ///
+    /// Returns `None` when dividing by zero.
+    pub const fn evaluate(&self, value: i16, operand: i16) -> Option<i16> {
+        Some(match self {
+            Self::Add => value.wrapping_add(operand),
+            Self::Sub => value.wrapping_sub(operand),
+            Self::Mul => value.wrapping_mul(operand),
+            Self::Div | Self::Mod if operand == 0 => return None,
+            Self::Div => value.wrapping_div(operand),
+            Self::Mod => value.wrapping_rem(operand),
+            Self::Or => value | operand,
+            Self::And => value & operand,
+            Self::Xor => value ^ operand,
+            Self::Not => !value,
+            Self::Shl => value.wrapping_shl(operand as u32),
+            Self::Shr => (value as u16).wrapping_shr(operand as u32) as i16,
+            Self::Sar => value.wrapping_shr(operand as u32),
+        })
+    }
+
+    /// The C compound assignment operator for this operation
+    ///
+    /// Work variables are signed, so `>>=` is an arithmetic shift. `Shr` and `Not` have no compound
+    /// assignment and return `None`.
+    pub const fn assign_symbol(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Add => "+=",
+            Self::Sub => "-=",
+            Self::Mul => "*=",
+            Self::Div => "/=",
+            Self::Mod => "%=",
+            Self::Or => "|=",
+            Self::And => "&=",
+            Self::Xor => "^=",
+            Self::Not | Self::Shr => return None,
+            Self::Shl => "<<=",
+            Self::Sar => ">>=",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        assert_eq!(CompareOp::try_from(2).unwrap(), CompareOp::Ge);
+        assert!(CompareOp::try_from(7).is_err());
+        assert!(CompareOp::And.evaluate(0b1010, 0b0010));
+        assert!(!CompareOp::Lt.evaluate(3, 3));
+
+        assert_eq!(u8::from(CalcOp::Sar), 11);
+        assert_eq!(CalcOp::Sub.evaluate(5, 7), Some(-2));
+        assert_eq!(CalcOp::Shr.evaluate(-1, 12), Some(0xf));
+        assert_eq!(CalcOp::Sar.evaluate(-16, 2), Some(-4));
+        assert_eq!(CalcOp::Add.evaluate(i16::MAX, 1), Some(i16::MIN));
+        assert_eq!(CalcOp::Div.evaluate(1, 0), None);
+        assert_eq!(CalcOp::Sar.assign_symbol(), Some(">>="));
+        assert_eq!(CalcOp::Shr.assign_symbol(), None);
+    }
+}
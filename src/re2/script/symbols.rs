@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use super::Instruction;
+
+const COMMENT_CHAR: char = ';';
+const WORK_KEYWORD: &str = "work";
+const RE2_SYMBOLS: &str = include_str!("re2_symbols.txt");
+
+/// A bank of game flags, as referenced by the `flag` field of `Ck` and `Set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum FlagBank {
+    System = 0,
+    Status = 1,
+    Stop = 2,
+    Scenario = 3,
+    Common = 4,
+    Room = 5,
+    Enemy = 6,
+    Enemy2 = 7,
+    Item = 8,
+    Map = 9,
+    Use = 10,
+    Message = 11,
+    RoomEnemy = 12,
+    PlayerBullet = 13,
+    EnemyBullet = 14,
+    Zapping = 15,
+    Key = 16,
+    MapC = 17,
+    MapI = 18,
+    Item2 = 19,
+}
+
+impl FlagBank {
+    pub const ALL: [Self; 20] = [
+        Self::System, Self::Status, Self::Stop, Self::Scenario, Self::Common, Self::Room, Self::Enemy,
+        Self::Enemy2, Self::Item, Self::Map, Self::Use, Self::Message, Self::RoomEnemy, Self::PlayerBullet,
+        Self::EnemyBullet, Self::Zapping, Self::Key, Self::MapC, Self::MapI, Self::Item2,
+    ];
+
+    /// The name used for this bank in symbol files and formatted flags
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Status => "status",
+            Self::Stop => "stop",
+            Self::Scenario => "scenario",
+            Self::Common => "common",
+            Self::Room => "room",
+            Self::Enemy => "enemy",
+            Self::Enemy2 => "enemy2",
+            Self::Item => "item",
+            Self::Map => "map",
+            Self::Use => "use",
+            Self::Message => "message",
+            Self::RoomEnemy => "room_enemy",
+            Self::PlayerBullet => "player_bullet",
+            Self::EnemyBullet => "enemy_bullet",
+            Self::Zapping => "zapping",
+            Self::Key => "key",
+            Self::MapC => "map_c",
+            Self::MapI => "map_i",
+            Self::Item2 => "item2",
+        }
+    }
+
+    /// Look up a bank by its name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|bank| bank.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The name of a bank number, or `bank<n>` if it isn't a known bank
+    pub fn name_of(bank: u8) -> String {
+        match Self::try_from(bank) {
+            Ok(bank) => bank.name().to_string(),
+            Err(_) => format!("bank{}", bank),
+        }
+    }
+
+    /// Format a flag as `bank[0xID]`
+    pub fn format_flag_id(bank: u8, id: u16) -> String {
+        format!("{}[0x{:02X}]", Self::name_of(bank), id)
+    }
+}
+
+/// User-facing names for game flags and work variables
+///
+/// Symbol files are plain text with one symbol per line. A flag is named with its bank, its id,
+/// and then the name, and a work variable with `work`, its index, and then the name. Banks may be
+/// given by [`FlagBank`] name or by number, and numbers may be decimal or `0x`-prefixed
+/// hexadecimal. The name runs to the end of the line and may be quoted. `;` starts a comment.
+///
+/// ```text
+/// ; flags
+/// scenario 0x2A "met Ada"
+/// 3 0x2B met Sherry
+/// ; work variables
+/// work 4 cutscene counter
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    flags: HashMap<(u8, u8), String>,
+    work: HashMap<u8, String>,
+}
+
+fn parse_number(s: &str) -> Option<u8> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The symbols bundled with this crate for RE2
+    pub fn re2() -> Self {
+        Self::parse(RE2_SYMBOLS).expect("Bundled RE2 symbols should be valid")
+    }
+
+    /// Parse a symbol file
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut table = Self::new();
+        table.extend_from_str(text)?;
+        Ok(table)
+    }
+
+    /// Load a symbol file from disk
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read symbol file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Failed to parse symbol file {}", path.display()))
+    }
+
+    /// Add the symbols from a symbol file, replacing any existing names for the same flags or
+    /// variables
+    pub fn extend_from_str(&mut self, text: &str) -> Result<()> {
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split_once(COMMENT_CHAR).map_or(line, |(code, _)| code).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let (Some(kind), Some(index), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+                bail!("Line {}: expected a bank or \"{}\", an index, and a name", line_number, WORK_KEYWORD);
+            };
+
+            let index = parse_number(index).ok_or_else(|| anyhow!("Line {}: invalid index {}", line_number, index))?;
+            let name = name.trim();
+            let name = name.strip_prefix('"').and_then(|name| name.strip_suffix('"')).unwrap_or(name).to_string();
+            if kind.eq_ignore_ascii_case(WORK_KEYWORD) {
+                self.work.insert(index, name);
+            } else {
+                let bank = FlagBank::from_name(kind)
+                    .map(u8::from)
+                    .or_else(|| parse_number(kind))
+                    .ok_or_else(|| anyhow!("Line {}: unknown flag bank {}", line_number, kind))?;
+                self.flags.insert((bank, index), name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the symbols from another table, replacing any existing names for the same flags or
+    /// variables
+    pub fn extend(&mut self, other: Self) {
+        self.flags.extend(other.flags);
+        self.work.extend(other.work);
+    }
+
+    pub fn flag_name(&self, bank: u8, id: u8) -> Option<&str> {
+        self.flags.get(&(bank, id)).map(String::as_str)
+    }
+
+    pub fn set_flag_name(&mut self, bank: u8, id: u8, name: impl Into<String>) {
+        self.flags.insert((bank, id), name.into());
+    }
+
+    pub fn work_name(&self, index: u8) -> Option<&str> {
+        self.work.get(&index).map(String::as_str)
+    }
+
+    pub fn set_work_name(&mut self, index: u8, name: impl Into<String>) {
+        self.work.insert(index, name.into());
+    }
+
+    /// Format a flag as `bank[id]`, followed by its quoted name if it has one
+    pub fn format_flag(&self, bank: u8, id: u8) -> String {
+        let flag = FlagBank::format_flag_id(bank, id as u16);
+        match self.flag_name(bank, id) {
+            Some(name) => format!("{} \"{}\"", flag, name),
+            None => flag,
+        }
+    }
+
+    /// Format a work variable as `work[index]`, followed by its quoted name if it has one
+    pub fn format_work(&self, index: u8) -> String {
+        match self.work_name(index) {
+            Some(name) => format!("work[{}] \"{}\"", index, name),
+            None => format!("work[{}]", index),
+        }
+    }
+
+    /// Format the flag or work variable an instruction refers to, if it refers to one
+    ///
+    /// `Ck` and `Set` refer to a flag, and `Cmp`, `Calc`, and `Calc2` to a work variable.
+    pub fn format_operand(&self, instruction: &Instruction) -> Option<String> {
+        match *instruction {
+            Instruction::Ck { flag, id, .. } | Instruction::Set { flag, id, .. } => Some(self.format_flag(flag, id)),
+            Instruction::Cmp { member: index, .. } | Instruction::Calc { flag: index, .. } | Instruction::Calc2 { flag: index, .. } => {
+                Some(self.format_work(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe an instruction by its mnemonic and the flag or work variable it refers to, e.g.
+    /// `ck scenario[0x2A] "met Ada"`
+    pub fn describe(&self, instruction: &Instruction) -> Option<String> {
+        self.format_operand(instruction).map(|operand| format!("{} {}", instruction.info().mnemonic, operand))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_bank() {
+        assert_eq!(FlagBank::try_from(3).unwrap(), FlagBank::Scenario);
+        assert_eq!(FlagBank::from_name("Room_Enemy"), Some(FlagBank::RoomEnemy));
+        assert_eq!(FlagBank::name_of(16), "key");
+        assert_eq!(FlagBank::name_of(200), "bank200");
+        assert_eq!(FlagBank::format_flag_id(5, 0x1f), "room[0x1F]");
+        for bank in FlagBank::ALL {
+            assert_eq!(FlagBank::from_name(bank.name()), Some(bank));
+        }
+    }
+
+    #[test]
+    fn test_symbol_table() {
+        let mut table = SymbolTable::re2();
+        table.extend(SymbolTable::parse("
+            ; user names
+            scenario 0x2A \"met Ada\"
+            4 7 door unlocked ; trailing comment
+            work 2 counter
+        ").unwrap());
+
+        assert_eq!(table.flag_name(FlagBank::Common.into(), 7), Some("door unlocked"));
+        assert_eq!(table.format_flag(FlagBank::Scenario.into(), 0x2A), "scenario[0x2A] \"met Ada\"");
+        assert_eq!(table.format_flag(25, 1), "bank25[0x01]");
+        assert_eq!(table.describe(&Instruction::Ck { flag: 3, id: 0x2A, on_off: 1 }).unwrap(), "ck scenario[0x2A] \"met Ada\"");
+        assert_eq!(table.describe(&Instruction::Calc2 { operator: 0, flag: 2, value: 3 }).unwrap(), "calc2 work[2] \"counter\"");
+        assert_eq!(table.describe(&Instruction::Sleep), None);
+
+        assert_eq!(table.format_flag(FlagBank::System.into(), 1), "system[0x01] \"scenario B\"");
+
+        assert!(SymbolTable::parse("scenario 0x2A").is_err());
+        assert!(SymbolTable::parse("nowhere 1 name").is_err());
+        assert!(SymbolTable::parse("room 256 name").is_err());
+    }
+}
@@ -0,0 +1,663 @@
+use super::{Field, FieldType, InstructionInfo, NUM_INSTRUCTIONS};
+
+/// Info for [`Instruction::Unknown`](super::Instruction::Unknown), whose bytes aren't split into fields
+pub(super) static UNKNOWN_INFO: InstructionInfo = InstructionInfo::new("Unknown", "unknown", &[]);
+
+/// Names and encoded fields of each instruction, indexed by opcode
+pub(super) static INSTRUCTION_INFO: [InstructionInfo; NUM_INSTRUCTIONS] = [
+    InstructionInfo::new("Nop", "nop", &[]),
+    InstructionInfo::new("EvtEnd", "evt_end", &[Field::new("value", FieldType::U8)]),
+    InstructionInfo::new("EvtNext", "evt_next", &[]),
+    InstructionInfo::new("EvtChain", "evt_chain", &[Field::new("id", FieldType::U8)]),
+    InstructionInfo::new("EvtExec", "evt_exec", &[
+        Field::new("data", FieldType::U8),
+        Field::new("go_sub", FieldType::U8),
+        Field::new("scd_id", FieldType::U8),
+    ]),
+    InstructionInfo::new("EvtKill", "evt_kill", &[Field::new("id", FieldType::U8)]),
+    InstructionInfo::new("IfElCk", "if_el_ck", &[Field::new("align", FieldType::U8), Field::new("size", FieldType::I16)]),
+    InstructionInfo::new("ElseCk", "else_ck", &[Field::new("align", FieldType::U8), Field::new("size", FieldType::I16)]),
+    InstructionInfo::new("EndIf", "end_if", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("Sleep", "sleep", &[]),
+    InstructionInfo::new("Sleeping", "sleeping", &[Field::new("count", FieldType::I16)]),
+    InstructionInfo::new("WSleep", "wsleep", &[]),
+    InstructionInfo::new("WSleeping", "wsleeping", &[]),
+    InstructionInfo::new("For", "for", &[
+        Field::new("align", FieldType::U8),
+        Field::new("size", FieldType::U16),
+        Field::new("count", FieldType::U16),
+    ]),
+    InstructionInfo::new("Next", "next", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("While", "while", &[Field::new("align", FieldType::U8), Field::new("size", FieldType::U16)]),
+    InstructionInfo::new("EWhile", "ewhile", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("Do", "do", &[Field::new("align", FieldType::U8), Field::new("size", FieldType::U16)]),
+    InstructionInfo::new("EdWhile", "ed_while", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("Switch", "switch", &[Field::new("id", FieldType::U8), Field::new("size", FieldType::U16)]),
+    InstructionInfo::new("Case", "case", &[
+        Field::new("align", FieldType::U8),
+        Field::new("size", FieldType::U16),
+        Field::new("value", FieldType::U16),
+    ]),
+    InstructionInfo::new("Default", "default", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("ESwitch", "eswitch", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("Goto", "goto", &[
+        Field::new("ifel_ctr", FieldType::U8),
+        Field::new("loop_ctr", FieldType::U8),
+        Field::new("align", FieldType::U8),
+        Field::new("offset", FieldType::I16),
+    ]),
+    InstructionInfo::new("GoSub", "go_sub", &[Field::new("id", FieldType::U8)]),
+    InstructionInfo::new("Return", "return", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("Break", "break", &[Field::new("align", FieldType::U8)]),
+    InstructionInfo::new("For2", "for2", &[
+        Field::new("align", FieldType::U8),
+        Field::new("start_value", FieldType::I16),
+        Field::new("align2", FieldType::U8),
+        Field::new("end_value", FieldType::I16),
+    ]),
+    InstructionInfo::new("BreakPoint", "break_point", &[]),
+    InstructionInfo::new("WorkCopy", "work_copy", &[
+        Field::new("source", FieldType::U8),
+        Field::new("destination", FieldType::U8),
+        Field::new("cast", FieldType::U8),
+    ]),
+    InstructionInfo::new("Nop1E", "nop1e", &[]),
+    InstructionInfo::new("Nop1F", "nop1f", &[]),
+    InstructionInfo::new("Nop20", "nop20", &[]),
+    InstructionInfo::new("Ck", "ck", &[
+        Field::new("flag", FieldType::U8),
+        Field::new("id", FieldType::U8),
+        Field::new("on_off", FieldType::U8),
+    ]),
+    InstructionInfo::new("Set", "set", &[
+        Field::new("flag", FieldType::U8),
+        Field::new("id", FieldType::U8),
+        Field::new("on_off", FieldType::U8),
+    ]),
+    InstructionInfo::new("Cmp", "cmp", &[
+        Field::new("align", FieldType::U8),
+        Field::new("member", FieldType::U8),
+        Field::new("operator", FieldType::U8),
+        Field::new("value", FieldType::I16),
+    ]),
+    InstructionInfo::new("Save", "save", &[Field::new("destination", FieldType::U8), Field::new("source", FieldType::I16)]),
+    InstructionInfo::new("Copy", "copy", &[Field::new("destination", FieldType::U8), Field::new("source", FieldType::U8)]),
+    InstructionInfo::new("Calc", "calc", &[
+        Field::new("align", FieldType::U8),
+        Field::new("operator", FieldType::U8),
+        Field::new("flag", FieldType::U8),
+        Field::new("value", FieldType::I16),
+    ]),
+    InstructionInfo::new("Calc2", "calc2", &[
+        Field::new("operator", FieldType::U8),
+        Field::new("flag", FieldType::U8),
+        Field::new("value", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceRnd", "sce_rnd", &[]),
+    InstructionInfo::new("CutChg", "cut_chg", &[Field::new("cut", FieldType::U8)]),
+    InstructionInfo::new("CutOld", "cut_old", &[]),
+    InstructionInfo::new("MessageOn", "message_on", &[
+        Field::new("align", FieldType::U8),
+        Field::new("type", FieldType::U8),
+        Field::new("message", FieldType::U8),
+        Field::new("display_time", FieldType::U16),
+    ]),
+    InstructionInfo::new("AotSet", "aot_set", &[
+        Field::new("aot", FieldType::I8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("w", FieldType::UFixed16),
+        Field::new("h", FieldType::UFixed16),
+        Field::new("data0", FieldType::U16),
+        Field::new("data1", FieldType::U16),
+        Field::new("data2", FieldType::U16),
+    ]),
+    InstructionInfo::new("ObjModelSet", "obj_model_set", &[
+        Field::new("md1", FieldType::U8),
+        Field::new("id", FieldType::U8),
+        Field::new("ccol_old", FieldType::U8),
+        Field::new("ccol_no", FieldType::U8),
+        Field::new("ctex_old", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("type", FieldType::U16),
+        Field::new("be_flag", FieldType::U16),
+        Field::new("attribute", FieldType::I16),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("dir_x", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+        Field::new("dir_z", FieldType::Fixed16),
+        Field::new("atari_offset_x", FieldType::Fixed16),
+        Field::new("atari_offset_y", FieldType::Fixed16),
+        Field::new("atari_offset_z", FieldType::Fixed16),
+        Field::new("atari_size_x", FieldType::Fixed16),
+        Field::new("atari_size_y", FieldType::Fixed16),
+        Field::new("atari_size_z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("WorkSet", "work_set", &[Field::new("type", FieldType::U8), Field::new("entity_id", FieldType::U8)]),
+    InstructionInfo::new("SpeedSet", "speed_set", &[Field::new("speed_id", FieldType::U8), Field::new("speed_value", FieldType::Fixed16)]),
+    InstructionInfo::new("AddSpeed", "add_speed", &[]),
+    InstructionInfo::new("AddASpeed", "add_aspeed", &[]),
+    InstructionInfo::new("PosSet", "pos_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("pos_x", FieldType::Fixed16),
+        Field::new("pos_y", FieldType::Fixed16),
+        Field::new("pos_z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("DirSet", "dir_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("dir_x", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+        Field::new("dir_z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("MemberSet", "member_set", &[Field::new("destination", FieldType::U8), Field::new("source", FieldType::I16)]),
+    InstructionInfo::new("MemberSet2", "member_set2", &[Field::new("destination", FieldType::U8), Field::new("source", FieldType::U8)]),
+    InstructionInfo::new("SeOn", "se_on", &[
+        Field::new("vab", FieldType::U8),
+        Field::new("edt", FieldType::I16),
+        Field::new("data0", FieldType::I16),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("ScaIdSet", "sca_id_set", &[Field::new("i_entry", FieldType::U8), Field::new("id", FieldType::U16)]),
+    InstructionInfo::new("FlrSet", "flr_set", &[Field::new("id", FieldType::U8), Field::new("flag", FieldType::U8)]),
+    InstructionInfo::new("DirCk", "dir_ck", &[
+        Field::new("align", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("add", FieldType::I16),
+    ]),
+    InstructionInfo::new("SceEsprOn", "sce_espr_on", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data0", FieldType::U16),
+        Field::new("data1", FieldType::U16),
+        Field::new("data2", FieldType::U16),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("DoorAotSet", "door_aot_set", &[
+        Field::new("aot", FieldType::U8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("w", FieldType::UFixed16),
+        Field::new("h", FieldType::UFixed16),
+        Field::new("next_pos_x", FieldType::Fixed16),
+        Field::new("next_pos_y", FieldType::Fixed16),
+        Field::new("next_pos_z", FieldType::Fixed16),
+        Field::new("next_cdir_y", FieldType::Fixed16),
+        Field::new("next_stage", FieldType::U8),
+        Field::new("next_room", FieldType::U8),
+        Field::new("next_cut", FieldType::U8),
+        Field::new("next_nfloor", FieldType::U8),
+        Field::new("dtex_type", FieldType::U8),
+        Field::new("door_type", FieldType::U8),
+        Field::new("knock_type", FieldType::U8),
+        Field::new("key_id", FieldType::U8),
+        Field::new("key_type", FieldType::U8),
+        Field::new("free", FieldType::U8),
+    ]),
+    InstructionInfo::new("CutAuto", "cut_auto", &[Field::new("on_off", FieldType::U8)]),
+    InstructionInfo::new("MemberCopy", "member_copy", &[Field::new("destination", FieldType::U8), Field::new("source", FieldType::U8)]),
+    InstructionInfo::new("MemberCmp", "member_cmp", &[
+        Field::new("align", FieldType::U8),
+        Field::new("flag", FieldType::U8),
+        Field::new("operator", FieldType::U8),
+        Field::new("value", FieldType::I16),
+    ]),
+    InstructionInfo::new("PlcMotion", "plc_motion", &[
+        Field::new("motion_id", FieldType::U8),
+        Field::new("mode", FieldType::U8),
+        Field::new("param", FieldType::U8),
+    ]),
+    InstructionInfo::new("PlcDest", "plc_dest", &[
+        Field::new("align", FieldType::U8),
+        Field::new("animation", FieldType::U8),
+        Field::new("bit", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("PlcNeck", "plc_neck", &[
+        Field::new("op", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("speed_x", FieldType::U8),
+        Field::new("speed_z", FieldType::U8),
+    ]),
+    InstructionInfo::new("PlcRet", "plc_ret", &[]),
+    InstructionInfo::new("PlcFlg", "plc_flg", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data0", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceEmSet", "sce_em_set", &[
+        Field::new("nop", FieldType::U8),
+        Field::new("em_no", FieldType::I8),
+        Field::new("id", FieldType::U8),
+        Field::new("type", FieldType::U16),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("sound_flg", FieldType::U8),
+        Field::new("model_type", FieldType::U8),
+        Field::new("em_set_flag", FieldType::U8),
+        Field::new("pos_x", FieldType::Fixed16),
+        Field::new("pos_y", FieldType::Fixed16),
+        Field::new("pos_z", FieldType::Fixed16),
+        Field::new("cdir_y", FieldType::Fixed16),
+        Field::new("motion", FieldType::I16),
+        Field::new("ctr_flg", FieldType::I16),
+    ]),
+    InstructionInfo::new("ColChgSet", "col_chg_set", &[
+        Field::new("data0", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+        Field::new("data2", FieldType::U8),
+        Field::new("data3", FieldType::U8),
+    ]),
+    InstructionInfo::new("AotReset", "aot_reset", &[
+        Field::new("aot", FieldType::I8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("data0", FieldType::I16),
+        Field::new("data1", FieldType::I16),
+        Field::new("data2", FieldType::I16),
+    ]),
+    InstructionInfo::new("AotOn", "aot_on", &[Field::new("aot", FieldType::I8)]),
+    InstructionInfo::new("SuperSet", "super_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("work", FieldType::U8),
+        Field::new("id", FieldType::U8),
+        Field::new("p_x", FieldType::Fixed16),
+        Field::new("p_y", FieldType::Fixed16),
+        Field::new("p_z", FieldType::Fixed16),
+        Field::new("d_x", FieldType::Fixed16),
+        Field::new("d_y", FieldType::Fixed16),
+        Field::new("d_z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("SuperReset", "super_reset", &[
+        Field::new("align", FieldType::U8),
+        Field::new("d_x", FieldType::Fixed16),
+        Field::new("d_y", FieldType::Fixed16),
+        Field::new("d_z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("PlcGun", "plc_gun", &[Field::new("value", FieldType::U8)]),
+    InstructionInfo::new("CutReplace", "cut_replace", &[Field::new("id", FieldType::U8), Field::new("value", FieldType::U8)]),
+    InstructionInfo::new("SceEsprKill", "sce_espr_kill", &[
+        Field::new("id", FieldType::U8),
+        Field::new("tp", FieldType::U8),
+        Field::new("work_kind", FieldType::I8),
+        Field::new("work_no", FieldType::I8),
+    ]),
+    InstructionInfo::new("DoorModelSet", "door_model_set", &[
+        Field::new("data0", FieldType::U8),
+        Field::new("id", FieldType::U8),
+        Field::new("ofs_y", FieldType::U8),
+        Field::new("be_flg", FieldType::U8),
+        Field::new("data5", FieldType::U8),
+        Field::new("data6", FieldType::U16),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+        Field::new("data10", FieldType::U16),
+        Field::new("data11", FieldType::U16),
+        Field::new("data12", FieldType::U16),
+    ]),
+    InstructionInfo::new("ItemAotSet", "item_aot_set", &[
+        Field::new("aot", FieldType::U8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("w", FieldType::UFixed16),
+        Field::new("h", FieldType::UFixed16),
+        Field::new("i_item", FieldType::U16),
+        Field::new("n_item", FieldType::U16),
+        Field::new("flag", FieldType::U16),
+        Field::new("md1", FieldType::U8),
+        Field::new("action", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceKeyCk", "sce_key_ck", &[Field::new("flag", FieldType::U8), Field::new("value", FieldType::U16)]),
+    InstructionInfo::new("SceTrgCk", "sce_trg_ck", &[Field::new("flag", FieldType::U8), Field::new("value", FieldType::U16)]),
+    InstructionInfo::new("SceBgmControl", "sce_bgm_control", &[
+        Field::new("id", FieldType::U8),
+        Field::new("op", FieldType::U8),
+        Field::new("type", FieldType::U8),
+        Field::new("vol_l", FieldType::U8),
+        Field::new("vol_r", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceEsprControl", "sce_espr_control", &[
+        Field::new("id", FieldType::U8),
+        Field::new("type", FieldType::U8),
+        Field::new("return", FieldType::U8),
+        Field::new("work_kind", FieldType::I8),
+        Field::new("work_no", FieldType::I8),
+    ]),
+    InstructionInfo::new("SceFadeSet", "sce_fade_set", &[
+        Field::new("data0", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+        Field::new("data2", FieldType::U8),
+        Field::new("data3", FieldType::U16),
+    ]),
+    InstructionInfo::new("SceEspr3dOn", "sce_espr3d_on", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data0", FieldType::U16),
+        Field::new("data1", FieldType::U16),
+        Field::new("data2", FieldType::U16),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("dir_x", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+        Field::new("dir_z", FieldType::Fixed16),
+        Field::new("data3", FieldType::I16),
+    ]),
+    InstructionInfo::new("MemberCalc", "member_calc", &[
+        Field::new("operator", FieldType::U8),
+        Field::new("flag", FieldType::U16),
+        Field::new("value", FieldType::I16),
+    ]),
+    InstructionInfo::new("MemberCalc2", "member_calc2", &[
+        Field::new("operator", FieldType::U8),
+        Field::new("flag", FieldType::U8),
+        Field::new("value", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceBgmTblSet", "sce_bgm_tbl_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("stage", FieldType::U8),
+        Field::new("room", FieldType::U8),
+        Field::new("data1", FieldType::U16),
+        Field::new("data2", FieldType::U16),
+    ]),
+    InstructionInfo::new("PlcRot", "plc_rot", &[Field::new("id", FieldType::U8), Field::new("sce_free0", FieldType::U16)]),
+    InstructionInfo::new("XaOn", "xa_on", &[Field::new("mode", FieldType::U8), Field::new("number", FieldType::U16)]),
+    InstructionInfo::new("WeaponChg", "weapon_chg", &[Field::new("weapon", FieldType::U8)]),
+    InstructionInfo::new("PlcCnt", "plc_cnt", &[Field::new("value", FieldType::U8)]),
+    InstructionInfo::new("SceShakeOn", "sce_shake_on", &[Field::new("slide_ofs", FieldType::I8), Field::new("copy_ofs", FieldType::I8)]),
+    InstructionInfo::new("MizuDivSet", "mizu_div_set", &[Field::new("value", FieldType::U8)]),
+    InstructionInfo::new("KeepItemCk", "keep_item_ck", &[Field::new("item", FieldType::U8)]),
+    InstructionInfo::new("XaVol", "xa_vol", &[Field::new("volume", FieldType::U8)]),
+    InstructionInfo::new("KageSet", "kage_set", &[
+        Field::new("work", FieldType::U8),
+        Field::new("id", FieldType::I8),
+        Field::new("data0", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+        Field::new("data2", FieldType::U8),
+        Field::new("data3", FieldType::U16),
+        Field::new("data4", FieldType::U16),
+        Field::new("data5", FieldType::U16),
+        Field::new("data16", FieldType::U16),
+    ]),
+    InstructionInfo::new("CutBeSet", "cut_be_set", &[
+        Field::new("id", FieldType::U8),
+        Field::new("value", FieldType::U8),
+        Field::new("on_off", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceItemLost", "sce_item_lost", &[Field::new("item", FieldType::U8)]),
+    InstructionInfo::new("PlcGunEff", "plc_gun_eff", &[]),
+    InstructionInfo::new("SceEsprOn2", "sce_espr_on2", &[
+        Field::new("dir_y_id2", FieldType::U8),
+        Field::new("data1", FieldType::U16),
+        Field::new("work_kind", FieldType::U8),
+        Field::new("work_no", FieldType::U8),
+        Field::new("data3", FieldType::U16),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::UFixed16),
+    ]),
+    InstructionInfo::new("SceEsprKill2", "sce_espr_kill2", &[Field::new("id", FieldType::U8)]),
+    InstructionInfo::new("PlcStop", "plc_stop", &[]),
+    InstructionInfo::new("AotSet4p", "aot_set4p", &[
+        Field::new("aot", FieldType::U8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x0", FieldType::Fixed16),
+        Field::new("z0", FieldType::Fixed16),
+        Field::new("x1", FieldType::Fixed16),
+        Field::new("z1", FieldType::Fixed16),
+        Field::new("x2", FieldType::Fixed16),
+        Field::new("z2", FieldType::Fixed16),
+        Field::new("x3", FieldType::Fixed16),
+        Field::new("z3", FieldType::Fixed16),
+        Field::new("data0", FieldType::U16),
+        Field::new("data1", FieldType::U16),
+        Field::new("data2", FieldType::U16),
+    ]),
+    InstructionInfo::new("DoorAotSet4p", "door_aot_set4p", &[
+        Field::new("aot", FieldType::U8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x0", FieldType::Fixed16),
+        Field::new("z0", FieldType::Fixed16),
+        Field::new("x1", FieldType::Fixed16),
+        Field::new("z1", FieldType::Fixed16),
+        Field::new("x2", FieldType::Fixed16),
+        Field::new("z2", FieldType::Fixed16),
+        Field::new("x3", FieldType::Fixed16),
+        Field::new("z3", FieldType::Fixed16),
+        Field::new("next_pos_x", FieldType::Fixed16),
+        Field::new("next_pos_y", FieldType::Fixed16),
+        Field::new("next_pos_z", FieldType::Fixed16),
+        Field::new("next_cdir_y", FieldType::Fixed16),
+        Field::new("next_stage", FieldType::U8),
+        Field::new("next_room", FieldType::U8),
+        Field::new("next_cut", FieldType::U8),
+        Field::new("next_nfloor", FieldType::U8),
+        Field::new("dtex_type", FieldType::U8),
+        Field::new("door_type", FieldType::U8),
+        Field::new("knock_type", FieldType::U8),
+        Field::new("key_id", FieldType::U8),
+        Field::new("key_type", FieldType::U8),
+        Field::new("free", FieldType::U8),
+    ]),
+    InstructionInfo::new("ItemAotSet4p", "item_aot_set4p", &[
+        Field::new("aot", FieldType::U8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x0", FieldType::Fixed16),
+        Field::new("z0", FieldType::Fixed16),
+        Field::new("x1", FieldType::Fixed16),
+        Field::new("z1", FieldType::Fixed16),
+        Field::new("x2", FieldType::Fixed16),
+        Field::new("z2", FieldType::Fixed16),
+        Field::new("x3", FieldType::Fixed16),
+        Field::new("z3", FieldType::Fixed16),
+        Field::new("i_item", FieldType::U16),
+        Field::new("n_item", FieldType::U16),
+        Field::new("flag", FieldType::U16),
+        Field::new("md1", FieldType::U8),
+        Field::new("action", FieldType::U8),
+    ]),
+    InstructionInfo::new("LightPosSet", "light_pos_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("index", FieldType::U8),
+        Field::new("xyz", FieldType::U8),
+        Field::new("position", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("LightKidoSet", "light_kido_set", &[Field::new("index", FieldType::U8), Field::new("luminosity", FieldType::I16)]),
+    InstructionInfo::new("RbjReset", "rbj_reset", &[]),
+    InstructionInfo::new("SceScrMove", "sce_scr_move", &[Field::new("align", FieldType::U8), Field::new("scrl_y", FieldType::I16)]),
+    InstructionInfo::new("PartsSet", "parts_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("id", FieldType::I8),
+        Field::new("type", FieldType::I8),
+        Field::new("value", FieldType::I16),
+    ]),
+    InstructionInfo::new("MovieOn", "movie_on", &[Field::new("id", FieldType::U8)]),
+    InstructionInfo::new("SplcRet", "splc_ret", &[]),
+    InstructionInfo::new("SplcSce", "splc_sce", &[]),
+    InstructionInfo::new("SuperOn", "super_on", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data0", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+        Field::new("data2", FieldType::I16),
+        Field::new("data3", FieldType::I16),
+        Field::new("data4", FieldType::I16),
+        Field::new("data5", FieldType::I16),
+        Field::new("data6", FieldType::I16),
+        Field::new("data7", FieldType::I16),
+    ]),
+    InstructionInfo::new("MirrorSet", "mirror_set", &[
+        Field::new("flag", FieldType::U8),
+        Field::new("position", FieldType::U16),
+        Field::new("min", FieldType::U16),
+        Field::new("max", FieldType::U16),
+    ]),
+    InstructionInfo::new("SceFadeAdjust", "sce_fade_adjust", &[Field::new("data0", FieldType::U8), Field::new("data1", FieldType::I16)]),
+    InstructionInfo::new("SceEspr3dOn2", "sce_espr3d_on2", &[
+        Field::new("dir_y_id2", FieldType::U8),
+        Field::new("bit", FieldType::U16),
+        Field::new("data_4", FieldType::U16),
+        Field::new("data_6", FieldType::U16),
+        Field::new("data_8", FieldType::U16),
+        Field::new("data_a", FieldType::U16),
+        Field::new("data_c", FieldType::U16),
+        Field::new("data_e", FieldType::U16),
+        Field::new("data_10", FieldType::U16),
+        Field::new("data_12", FieldType::U16),
+        Field::new("data_14", FieldType::U16),
+    ]),
+    InstructionInfo::new("SceItemGet", "sce_item_get", &[Field::new("id", FieldType::U8), Field::new("num", FieldType::U8)]),
+    InstructionInfo::new("SceLineStart", "sce_line_start", &[Field::new("id", FieldType::U8), Field::new("value", FieldType::U16)]),
+    InstructionInfo::new("SceLineMain", "sce_line_main", &[
+        Field::new("id", FieldType::U8),
+        Field::new("data0", FieldType::I16),
+        Field::new("data1", FieldType::I16),
+    ]),
+    InstructionInfo::new("SceLineEnd", "sce_line_end", &[]),
+    InstructionInfo::new("ScePartsBomb", "sce_parts_bomb", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data2", FieldType::U8),
+        Field::new("data3", FieldType::U8),
+        Field::new("data4", FieldType::U8),
+        Field::new("data5", FieldType::U8),
+        Field::new("data6", FieldType::I16),
+        Field::new("data8", FieldType::I16),
+        Field::new("data_a", FieldType::I16),
+        Field::new("data_c", FieldType::I16),
+        Field::new("data_e", FieldType::I16),
+    ]),
+    InstructionInfo::new("ScePartsDown", "sce_parts_down", &[
+        Field::new("id", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("c_dir_z", FieldType::Fixed16),
+        Field::new("dir_x", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+        Field::new("dir_z", FieldType::Fixed16),
+    ]),
+    InstructionInfo::new("LightColorSet", "light_color_set", &[
+        Field::new("index", FieldType::U8),
+        Field::new("r", FieldType::U8),
+        Field::new("g", FieldType::U8),
+        Field::new("b", FieldType::U8),
+        Field::new("align", FieldType::U8),
+    ]),
+    InstructionInfo::new("LightPosSet2", "light_pos_set2", &[
+        Field::new("n_cut", FieldType::U8),
+        Field::new("index", FieldType::U8),
+        Field::new("xyz", FieldType::U8),
+        Field::new("position", FieldType::I16),
+    ]),
+    InstructionInfo::new("LightKidoSet2", "light_kido_set2", &[
+        Field::new("align", FieldType::U8),
+        Field::new("n_cut", FieldType::U8),
+        Field::new("index", FieldType::U8),
+        Field::new("luminosity", FieldType::U16),
+    ]),
+    InstructionInfo::new("LightColorSet2", "light_color_set2", &[
+        Field::new("n_cut", FieldType::U8),
+        Field::new("index", FieldType::U8),
+        Field::new("r", FieldType::U8),
+        Field::new("g", FieldType::U8),
+        Field::new("b", FieldType::U8),
+    ]),
+    InstructionInfo::new("SeVol", "se_vol", &[Field::new("volume", FieldType::U8)]),
+    InstructionInfo::new("KeepItemCk2", "keep_item_ck2", &[Field::new("item_id", FieldType::U8), Field::new("quantity", FieldType::U8)]),
+    InstructionInfo::new("SceEsprTask", "sce_espr_task", &[Field::new("work_kind", FieldType::I8), Field::new("work_no", FieldType::I8)]),
+    InstructionInfo::new("PlcHeal", "plc_heal", &[]),
+    InstructionInfo::new("StMapHint", "st_map_hint", &[Field::new("value", FieldType::U8)]),
+    InstructionInfo::new("SceEmPosCk", "sce_em_pos_ck", &[
+        Field::new("id", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+        Field::new("att", FieldType::U8),
+        Field::new("flg", FieldType::U16),
+    ]),
+    InstructionInfo::new("PoisonCk", "poison_ck", &[]),
+    InstructionInfo::new("PoisonClr", "poison_clr", &[]),
+    InstructionInfo::new("SceItemLost2", "sce_item_lost2", &[Field::new("item_id", FieldType::U8), Field::new("quantity", FieldType::U8)]),
+    InstructionInfo::new("EvtNext2", "evt_next2", &[]),
+    InstructionInfo::new("VibSet0", "vib_set0", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data0", FieldType::U16),
+        Field::new("data1", FieldType::U16),
+    ]),
+    InstructionInfo::new("VibSet1", "vib_set1", &[
+        Field::new("id", FieldType::U8),
+        Field::new("value1", FieldType::U16),
+        Field::new("value2", FieldType::U16),
+    ]),
+    InstructionInfo::new("VibFadeSet", "vib_fade_set", &[
+        Field::new("align", FieldType::U8),
+        Field::new("data0", FieldType::U8),
+        Field::new("data1", FieldType::U8),
+        Field::new("data2", FieldType::U16),
+        Field::new("data3", FieldType::U16),
+    ]),
+    InstructionInfo::new("ItemAotSet2", "item_aot_set2", &[
+        Field::new("aot", FieldType::U8),
+        Field::new("sce", FieldType::U8),
+        Field::new("sat", FieldType::U8),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("super", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("w", FieldType::UFixed16),
+        Field::new("h", FieldType::UFixed16),
+        Field::new("i_item", FieldType::U16),
+        Field::new("n_item", FieldType::U16),
+        Field::new("flag", FieldType::U16),
+        Field::new("md1", FieldType::U8),
+        Field::new("action", FieldType::U8),
+        Field::new("data16", FieldType::U8),
+        Field::new("data17", FieldType::U8),
+    ]),
+    InstructionInfo::new("SceEmSet2", "sce_em_set2", &[
+        Field::new("align", FieldType::U8),
+        Field::new("aot", FieldType::U8),
+        Field::new("emd", FieldType::U8),
+        Field::new("type", FieldType::U16),
+        Field::new("n_floor", FieldType::U8),
+        Field::new("se_type", FieldType::U8),
+        Field::new("model_type", FieldType::U8),
+        Field::new("em_set_flag", FieldType::U8),
+        Field::new("x", FieldType::Fixed16),
+        Field::new("y", FieldType::Fixed16),
+        Field::new("z", FieldType::Fixed16),
+        Field::new("dir_y", FieldType::Fixed16),
+        Field::new("timer0", FieldType::U16),
+        Field::new("timer1", FieldType::U16),
+        Field::new("data16", FieldType::U16),
+    ]),
+];
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Result};
+
+use super::{Instruction, ScriptKind, MAX_OPCODE};
+
+/// A problem found in a script function by [`ScriptIssue::check_function`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptIssueKind {
+    /// A block opened by this instruction is never closed
+    UnclosedBlock,
+    /// This instruction closes or continues a block that isn't open
+    UnexpectedBlockEnd {
+        /// The mnemonic of the innermost open block, if any
+        open: Option<&'static str>,
+    },
+    /// A block size or branch offset doesn't land on the start of an instruction
+    BranchNotOnBoundary { target: isize },
+    /// A `Goto` jumps outside of the function
+    GotoOutsideFunction { target: isize },
+    /// The function doesn't end with `EvtEnd`
+    MissingEvtEnd,
+    /// An AOT index is not less than the number of AOTs in the room header
+    AotOutOfRange { aot: i32, num_aots: usize },
+    /// An opcode above [`MAX_OPCODE`]
+    InvalidOpcode { opcode: u8 },
+    /// An instruction with a known opcode that couldn't be decoded
+    UndecodedInstruction { opcode: u8 },
+}
+
+impl Display for ScriptIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::UnclosedBlock => write!(f, "block is never closed"),
+            Self::UnexpectedBlockEnd { open: Some(open) } => write!(f, "does not match the open {} block", open),
+            Self::UnexpectedBlockEnd { open: None } => write!(f, "no block is open"),
+            Self::BranchNotOnBoundary { target } => write!(f, "branch target {:#x} is not an instruction boundary", target),
+            Self::GotoOutsideFunction { target } => write!(f, "goto target {:#x} is outside the function", target),
+            Self::MissingEvtEnd => write!(f, "function does not end with evt_end"),
+            Self::AotOutOfRange { aot, num_aots } => write!(f, "AOT {} is out of range for a room with {} AOTs", aot, num_aots),
+            Self::InvalidOpcode { opcode } => write!(f, "invalid opcode {:#04x}", opcode),
+            Self::UndecodedInstruction { opcode } => write!(f, "opcode {:#04x} could not be decoded", opcode),
+        }
+    }
+}
+
+/// A problem found in a script, along with where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptIssue {
+    pub script: ScriptKind,
+    pub function: usize,
+    /// Index of the instruction in the function
+    pub index: usize,
+    /// Byte offset of the instruction from the start of the function
+    pub offset: usize,
+    pub kind: ScriptIssueKind,
+}
+
+impl ScriptIssue {
+    /// The AOT index an instruction refers to, if it refers to one
+    fn aot_index(instruction: &Instruction) -> Option<i32> {
+        match instruction {
+            Instruction::AotSet { .. } | Instruction::AotSet4p { .. } | Instruction::DoorAotSet { .. }
+            | Instruction::DoorAotSet4p { .. } | Instruction::ItemAotSet { .. } | Instruction::ItemAotSet4p { .. }
+            | Instruction::ItemAotSet2 { .. } | Instruction::AotReset { .. } | Instruction::AotOn(_) => instruction.field_value("aot"),
+            _ => None,
+        }
+    }
+
+    /// Check a script function for problems
+    ///
+    /// `num_aots` is the number of AOTs the room has room for, as given in the RDT header.
+    pub fn check_function(script: ScriptKind, function_index: usize, function: &[Instruction], num_aots: usize) -> Vec<Self> {
+        let mut offsets = Vec::with_capacity(function.len() + 1);
+        let mut offset = 0usize;
+        for instruction in function {
+            offsets.push(offset);
+            offset += instruction.size();
+        }
+        let end = offset;
+        let boundaries: HashSet<_> = offsets.iter().copied().chain([end]).collect();
+
+        let mut issues = Vec::new();
+        let mut issue = |index: usize, kind| {
+            issues.push(Self { script, function: function_index, index, offset: offsets[index], kind });
+        };
+
+        let mut open_blocks: Vec<usize> = Vec::new();
+        for (i, instruction) in function.iter().enumerate() {
+            if let Instruction::Unknown { opcode, .. } = *instruction {
+                if opcode > MAX_OPCODE {
+                    issue(i, ScriptIssueKind::InvalidOpcode { opcode });
+                } else {
+                    issue(i, ScriptIssueKind::UndecodedInstruction { opcode });
+                }
+            }
+
+            let open = open_blocks.last().map(|&opener| &function[opener]);
+            let unexpected = ScriptIssueKind::UnexpectedBlockEnd { open: open.map(|opener| opener.info().mnemonic) };
+            match instruction {
+                Instruction::ElseCk { .. } if !matches!(open, Some(Instruction::IfElCk { .. })) => issue(i, unexpected),
+                Instruction::Case { .. } | Instruction::Default(_) if !matches!(open, Some(Instruction::Switch { .. })) => issue(i, unexpected),
+                _ if instruction.decreases_nesting() => {
+                    match open_blocks.iter().rposition(|&opener| instruction.closes(&function[opener])) {
+                        Some(position) => {
+                            // anything opened inside the block we're closing was never closed
+                            if position + 1 < open_blocks.len() {
+                                issue(i, unexpected);
+                            }
+                            for opener in open_blocks.drain(position..).skip(1) {
+                                issue(opener, ScriptIssueKind::UnclosedBlock);
+                            }
+                        }
+                        None => issue(i, unexpected),
+                    }
+                }
+                _ => (),
+            }
+
+            if instruction.increases_nesting() {
+                open_blocks.push(i);
+            }
+
+            if let Some(target) = instruction.branch_target(offsets[i]) {
+                let in_function = target >= 0 && target as usize <= end;
+                if matches!(instruction, Instruction::Goto { .. }) && !in_function {
+                    issue(i, ScriptIssueKind::GotoOutsideFunction { target });
+                } else if !in_function || !boundaries.contains(&(target as usize)) {
+                    issue(i, ScriptIssueKind::BranchNotOnBoundary { target });
+                }
+            }
+
+            if let Some(aot) = Self::aot_index(instruction)
+                && (aot < 0 || aot as usize >= num_aots) {
+                issue(i, ScriptIssueKind::AotOutOfRange { aot, num_aots });
+            }
+        }
+
+        for opener in open_blocks {
+            issue(opener, ScriptIssueKind::UnclosedBlock);
+        }
+
+        if !matches!(function.last(), Some(Instruction::EvtEnd(_))) {
+            let index = function.len().saturating_sub(1);
+            let offset = offsets.get(index).copied().unwrap_or_default();
+            issues.push(Self { script, function: function_index, index, offset, kind: ScriptIssueKind::MissingEvtEnd });
+        }
+
+        issues
+    }
+
+    /// Check every function in a script for problems
+    pub fn check_script(script: ScriptKind, functions: &[Vec<Instruction>], num_aots: usize) -> Vec<Self> {
+        functions.iter()
+            .enumerate()
+            .flat_map(|(i, function)| Self::check_function(script, i, function, num_aots))
+            .collect()
+    }
+}
+
+impl Display for ScriptIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{:?} function {} at {:#06x}: {}", self.script, self.function, self.offset, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(source: &str) -> Vec<ScriptIssueKind> {
+        let function = Instruction::assemble(source).unwrap();
+        ScriptIssue::check_function(ScriptKind::Exec, 0, &function, 4).into_iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn test_valid_function() {
+        let issues = check("
+            if_el_ck size=@end
+                ck flag=1 id=2 on_off=1
+                aot_on 3
+            end: end_if
+            evt_end
+        ");
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn test_issues() {
+        let function = Instruction::assemble("
+            if_el_ck size=3
+                for size=@next count=2
+                next: end_if
+            next
+            goto offset=-20
+            aot_on 4
+            unknown 0xa0 0
+        ").unwrap();
+        let issues = ScriptIssue::check_function(ScriptKind::Init, 1, &function, 4);
+        let kinds: Vec<_> = issues.iter().map(|issue| (issue.index, issue.kind.clone())).collect();
+        assert_eq!(kinds, vec![
+            (0, ScriptIssueKind::BranchNotOnBoundary { target: 3 }),
+            (2, ScriptIssueKind::UnexpectedBlockEnd { open: Some("for") }),
+            (1, ScriptIssueKind::UnclosedBlock),
+            (3, ScriptIssueKind::UnexpectedBlockEnd { open: None }),
+            (4, ScriptIssueKind::GotoOutsideFunction { target: 14 - 20 }),
+            (5, ScriptIssueKind::AotOutOfRange { aot: 4, num_aots: 4 }),
+            (6, ScriptIssueKind::InvalidOpcode { opcode: 0xA0 }),
+            (6, ScriptIssueKind::MissingEvtEnd),
+        ]);
+        assert_eq!(issues[0].to_string(), "Init function 1 at 0x0000: branch target 0x3 is not an instruction boundary");
+    }
+}
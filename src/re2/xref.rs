@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter, Result};
+
+use super::rdt::{Rdt, RoomId};
+use super::script::{CalcOp, CompareOp, FlagBank, Instruction, ScriptKind};
+
+/// A game variable that scripts read or write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GameVariable {
+    /// A flag in one of the [`FlagBank`]s
+    Flag { bank: u8, id: u16 },
+    /// A work variable, as compared by `Cmp` and written by `Save`, `Copy`, `Calc`, and `Calc2`
+    Work(u8),
+    /// Whether the player is carrying an item, as checked by `KeepItemCk` and `KeepItemCk2`
+    KeptItem(u8),
+}
+
+impl Display for GameVariable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Flag { bank, id } => f.write_str(&FlagBank::format_flag_id(*bank, *id)),
+            Self::Work(index) => write!(f, "work[{}]", index),
+            Self::KeptItem(item) => write!(f, "kept_item[{}]", item),
+        }
+    }
+}
+
+/// How an instruction uses a game variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableAccess {
+    /// `Ck` tests whether a flag is `on_off`
+    Check { on_off: u8 },
+    /// `Set` sets a flag to `on_off`
+    Set { on_off: u8 },
+    /// `Cmp` compares a work variable with a value
+    Compare { operator: u8, value: i16 },
+    /// `Save` sets a work variable to a value
+    Save { value: i16 },
+    /// `Copy` sets a work variable to the value of work variable `source`
+    Copy { source: u8 },
+    /// `Calc` combines a work variable with a value
+    Calculate { operator: u8, value: i16 },
+    /// `Calc2` combines a work variable with the value of work variable `source`
+    CalculateWork { operator: u8, source: u8 },
+    /// `KeepItemCk` or `KeepItemCk2` checks that the player has at least `quantity` of an item
+    KeepItem { quantity: u8 },
+    /// An item AOT is only placed while its pickup flag is off, and picking up the item sets it
+    ItemPickup { item: u16 },
+}
+
+impl VariableAccess {
+    /// Whether the game may change the variable as a result of this access
+    pub const fn is_write(&self) -> bool {
+        matches!(self,
+            Self::Set { .. } | Self::Save { .. } | Self::Copy { .. } | Self::Calculate { .. } | Self::CalculateWork { .. }
+            | Self::ItemPickup { .. }
+        )
+    }
+}
+
+impl Display for VariableAccess {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Check { on_off } => write!(f, "check == {}", on_off),
+            Self::Set { on_off } => write!(f, "set = {}", on_off),
+            Self::Compare { operator, value } => match CompareOp::try_from(*operator) {
+                Ok(op) => write!(f, "compare {} {}", op.symbol(), value),
+                Err(_) => write!(f, "compare (operator {}) {}", operator, value),
+            },
+            Self::Save { value } => write!(f, "save = {}", value),
+            Self::Copy { source } => write!(f, "copy = work[{}]", source),
+            Self::Calculate { operator, value } => match CalcOp::try_from(*operator) {
+                Ok(op) => write!(f, "calc {:?} {}", op, value),
+                Err(_) => write!(f, "calc (operator {}) {}", operator, value),
+            },
+            Self::CalculateWork { operator, source } => match CalcOp::try_from(*operator) {
+                Ok(op) => write!(f, "calc {:?} work[{}]", op, source),
+                Err(_) => write!(f, "calc (operator {}) work[{}]", operator, source),
+            },
+            Self::KeepItem { quantity } => write!(f, "keep item >= {}", quantity),
+            Self::ItemPickup { item } => write!(f, "pickup of item {}", item),
+        }
+    }
+}
+
+/// A single reference to a game variable from a room script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableReference {
+    pub room: RoomId,
+    pub script: ScriptKind,
+    pub function: usize,
+    /// Byte offset of the instruction from the start of the function
+    pub offset: usize,
+    pub access: VariableAccess,
+}
+
+impl Display for VariableReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} {:?} function {} at {:#06x}: {}", self.room, self.script, self.function, self.offset, self.access)
+    }
+}
+
+/// A game-wide index of where room scripts read and write flags, work variables, and items
+///
+/// References within each variable are kept in the order they were added, so adding rooms in order
+/// gives a listing ordered by room, script, function, and offset.
+#[derive(Debug, Clone, Default)]
+pub struct FlagIndex {
+    references: BTreeMap<GameVariable, Vec<VariableReference>>,
+}
+
+impl FlagIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index of the scripts of a set of rooms
+    pub fn build<'a, I: IntoIterator<Item = (RoomId, &'a Rdt)>>(rooms: I) -> Self {
+        let mut index = Self::new();
+        for (room, rdt) in rooms {
+            index.add_room(room, rdt);
+        }
+        index
+    }
+
+    /// The variable an instruction refers to and how it uses it, if it refers to one
+    pub fn variable_access(instruction: &Instruction) -> Option<(GameVariable, VariableAccess)> {
+        Some(match *instruction {
+            Instruction::Ck { flag, id, on_off } => (GameVariable::Flag { bank: flag, id: id as u16 }, VariableAccess::Check { on_off }),
+            Instruction::Set { flag, id, on_off } => (GameVariable::Flag { bank: flag, id: id as u16 }, VariableAccess::Set { on_off }),
+            Instruction::Cmp { member, operator, value, .. } => (GameVariable::Work(member), VariableAccess::Compare { operator, value }),
+            Instruction::Save { destination, source } => (GameVariable::Work(destination), VariableAccess::Save { value: source }),
+            Instruction::Copy { destination, source } => (GameVariable::Work(destination), VariableAccess::Copy { source }),
+            Instruction::Calc { operator, flag, value, .. } => (GameVariable::Work(flag), VariableAccess::Calculate { operator, value }),
+            Instruction::Calc2 { operator, flag, value } => {
+                (GameVariable::Work(flag), VariableAccess::CalculateWork { operator, source: value })
+            }
+            Instruction::KeepItemCk(item) => (GameVariable::KeptItem(item), VariableAccess::KeepItem { quantity: 1 }),
+            Instruction::KeepItemCk2 { item_id, quantity } => (GameVariable::KeptItem(item_id), VariableAccess::KeepItem { quantity }),
+            Instruction::ItemAotSet { i_item, flag, .. }
+            | Instruction::ItemAotSet4p { i_item, flag, .. }
+            | Instruction::ItemAotSet2 { i_item, flag, .. } => {
+                (GameVariable::Flag { bank: FlagBank::Item.into(), id: flag }, VariableAccess::ItemPickup { item: i_item })
+            }
+            _ => return None,
+        })
+    }
+
+    /// Add the references in a script function
+    pub fn add_function(&mut self, room: RoomId, script: ScriptKind, function_index: usize, function: &[Instruction]) {
+        let mut offset = 0usize;
+        for instruction in function {
+            if let Some((variable, access)) = Self::variable_access(instruction) {
+                self.references.entry(variable).or_default().push(VariableReference {
+                    room,
+                    script,
+                    function: function_index,
+                    offset,
+                    access,
+                });
+            }
+            offset += instruction.size();
+        }
+    }
+
+    /// Add the references in a room's init and exec scripts
+    pub fn add_room(&mut self, room: RoomId, rdt: &Rdt) {
+        for (i, function) in rdt.init_script().enumerate() {
+            self.add_function(room, ScriptKind::Init, i, function);
+        }
+        for (i, function) in rdt.exec_script().enumerate() {
+            self.add_function(room, ScriptKind::Exec, i, function);
+        }
+    }
+
+    /// Every reference to a variable
+    pub fn references(&self, variable: GameVariable) -> &[VariableReference] {
+        self.references.get(&variable).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every reference to a flag
+    pub fn flag(&self, bank: u8, id: u16) -> &[VariableReference] {
+        self.references(GameVariable::Flag { bank, id })
+    }
+
+    /// Every reference to any flag in a bank, ordered by flag id
+    pub fn bank(&self, bank: u8) -> impl Iterator<Item = (u16, &VariableReference)> {
+        let range = GameVariable::Flag { bank, id: 0 }..=GameVariable::Flag { bank, id: u16::MAX };
+        self.references.range(range).flat_map(|(variable, references)| {
+            let GameVariable::Flag { id, .. } = *variable else { unreachable!() };
+            references.iter().map(move |reference| (id, reference))
+        })
+    }
+
+    /// The rooms that write a variable
+    pub fn rooms_writing(&self, variable: GameVariable) -> BTreeSet<RoomId> {
+        self.references(variable).iter().filter(|r| r.access.is_write()).map(|r| r.room).collect()
+    }
+
+    /// The rooms that read a variable
+    pub fn rooms_reading(&self, variable: GameVariable) -> BTreeSet<RoomId> {
+        self.references(variable).iter().filter(|r| !r.access.is_write()).map(|r| r.room).collect()
+    }
+
+    /// Every variable in the index, in order, with its references
+    pub fn iter(&self) -> impl Iterator<Item = (GameVariable, &[VariableReference])> {
+        self.references.iter().map(|(variable, references)| (*variable, references.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::re2::SymbolTable;
+
+    #[test]
+    fn test_flag_index() {
+        let function = Instruction::assemble("
+            if_el_ck size=@end
+                ck flag=Scenario id=0x2a on_off=0
+                cmp member=3 operator=Ge value=2
+                keep_item_ck 5
+                calc operator=Add flag=3 value=1
+                set flag=Scenario id=0x2a on_off=1
+            end: end_if
+            item_aot_set aot=1 i_item=HandgunAmmo n_item=15 flag=300
+            evt_end
+        ").unwrap();
+        let other = Instruction::assemble("
+            set flag=Scenario id=0x2a on_off=0
+            set flag=Scenario id=0x10 on_off=1
+            evt_end
+        ").unwrap();
+
+        let room_100 = RoomId::new(0, 0);
+        let room_201 = RoomId::new(1, 1);
+        let mut index = FlagIndex::new();
+        index.add_function(room_100, ScriptKind::Init, 0, &function);
+        index.add_function(room_201, ScriptKind::Exec, 2, &other);
+
+        let scenario = u8::from(FlagBank::Scenario);
+        let references = index.flag(scenario, 0x2a);
+        assert_eq!(references.len(), 3);
+        assert_eq!(references[0], VariableReference {
+            room: room_100,
+            script: ScriptKind::Init,
+            function: 0,
+            offset: 4,
+            access: VariableAccess::Check { on_off: 0 },
+        });
+        assert_eq!(references[2].to_string(), "201 Exec function 2 at 0x0000: set = 0");
+
+        let met_ada = GameVariable::Flag { bank: scenario, id: 0x2a };
+        assert_eq!(met_ada.to_string(), SymbolTable::new().format_flag(scenario, 0x2a));
+        assert_eq!(index.rooms_writing(met_ada), BTreeSet::from([room_100, room_201]));
+        assert_eq!(index.rooms_reading(met_ada), BTreeSet::from([room_100]));
+        assert_eq!(index.references(GameVariable::Work(3))[0].access, VariableAccess::Compare { operator: 2, value: 2 });
+        assert_eq!(index.references(GameVariable::Work(3))[1].to_string(), "100 Init function 0 at 0x0010: calc Add 1");
+        assert_eq!(index.rooms_writing(GameVariable::Work(3)), BTreeSet::from([room_100]));
+        assert_eq!(index.references(GameVariable::KeptItem(5)).len(), 1);
+        assert_eq!(index.flag(FlagBank::Item.into(), 300)[0].access, VariableAccess::ItemPickup { item: 20 });
+
+        let bank: Vec<_> = index.bank(scenario).map(|(id, reference)| (id, reference.room)).collect();
+        assert_eq!(bank, vec![(0x10, room_201), (0x2a, room_100), (0x2a, room_100), (0x2a, room_201)]);
+        assert_eq!(index.len(), 5);
+    }
+}